@@ -0,0 +1,553 @@
+//!Audio channels
+//!
+//!Each channel has its own volume and plays sounds through `Backend`.
+//!Sounds are played by `Device` on default output of amethyst's audio, which is picked on start.
+//!When there is no sound device, `Null` backend is used, which only tracks what should be playing,
+//!so game continues without sound.
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+const AUDIO_DIR: &'static str = "assets/audio";
+pub const DEFAULT_VOLUME: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Channel {
+    ///Background music, only one track is played at a time.
+    Bgm,
+    ///Sound effects, which can overlap each other.
+    Sfx,
+    Voice,
+    ///Background sounds like rain, only one track is played at a time.
+    Ambient,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [Channel::Bgm, Channel::Sfx, Channel::Voice, Channel::Ambient];
+
+    ///Returns channel by its name in script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bgm" => Some(Channel::Bgm),
+            "sfx" => Some(Channel::Sfx),
+            "voice" => Some(Channel::Voice),
+            "ambient" => Some(Channel::Ambient),
+            _ => None,
+        }
+    }
+
+    ///Returns whether channel plays sounds on top of each other.
+    fn is_mixed(self) -> bool {
+        self == Channel::Sfx
+    }
+
+    ///Returns whether sounds are looped unless specified otherwise.
+    pub fn is_looped(self) -> bool {
+        match self {
+            Channel::Bgm | Channel::Ambient => true,
+            Channel::Sfx | Channel::Voice => false,
+        }
+    }
+}
+
+///Identifier of sound in backend.
+pub type SoundId = u64;
+
+///Plays sound files.
+pub trait Backend {
+    ///Starts to play `assets/audio/{file}`.
+    ///
+    ///Returns `false` if file cannot be played.
+    fn play(&mut self, id: SoundId, file: &str, looping: bool, volume: f32) -> bool;
+    fn set_volume(&mut self, id: SoundId, volume: f32);
+    fn stop(&mut self, id: SoundId);
+    ///Returns whether sound is still playing.
+    fn is_playing(&self, id: SoundId) -> bool;
+    ///Called once per frame.
+    fn update(&mut self);
+}
+
+///Backend without sound device.
+///
+///Looped sounds are considered to be playing until stopped, while the rest are finished right away.
+#[derive(Default)]
+pub struct Null {
+    playing: HashSet<SoundId>,
+}
+
+impl Backend for Null {
+    fn play(&mut self, id: SoundId, _: &str, looping: bool, _: f32) -> bool {
+        if looping {
+            self.playing.insert(id);
+        }
+        true
+    }
+
+    fn set_volume(&mut self, _: SoundId, _: f32) {
+    }
+
+    fn stop(&mut self, id: SoundId) {
+        self.playing.remove(&id);
+    }
+
+    fn is_playing(&self, id: SoundId) -> bool {
+        self.playing.contains(&id)
+    }
+
+    fn update(&mut self) {
+    }
+}
+
+struct Playing {
+    sink: amethyst::audio::AudioSink,
+    file: String,
+    looping: bool,
+}
+
+///Backend that plays sounds on output device.
+pub struct Device {
+    output: amethyst::audio::output::Output,
+    //Decoded lazily by sink, so only file content is cached
+    sources: HashMap<String, amethyst::audio::Source>,
+    playing: HashMap<SoundId, Playing>,
+}
+
+impl Device {
+    pub fn new(output: amethyst::audio::output::Output) -> Self {
+        Self {
+            output,
+            sources: HashMap::new(),
+            playing: HashMap::new(),
+        }
+    }
+
+    fn source(&mut self, file: &str) -> Option<&amethyst::audio::Source> {
+        if !self.sources.contains_key(file) {
+            let path = format!("{}/{}", AUDIO_DIR, file);
+            match fs::read(&path) {
+                Ok(bytes) => {
+                    self.sources.insert(file.to_owned(), amethyst::audio::Source { bytes });
+                },
+                Err(error) => {
+                    warn!("Unable to read {}: {}", path, error);
+                    return None;
+                }
+            }
+        }
+
+        self.sources.get(file)
+    }
+}
+
+impl Backend for Device {
+    fn play(&mut self, id: SoundId, file: &str, looping: bool, volume: f32) -> bool {
+        let sink = amethyst::audio::AudioSink::new(&self.output);
+        let source = match self.source(file) {
+            Some(source) => source,
+            None => return false,
+        };
+
+        if let Err(error) = sink.append(source) {
+            warn!("Unable to play {}: {}", file, error);
+            return false;
+        }
+        sink.set_volume(volume);
+
+        self.playing.insert(id, Playing {
+            sink,
+            file: file.to_owned(),
+            looping,
+        });
+        true
+    }
+
+    fn set_volume(&mut self, id: SoundId, volume: f32) {
+        if let Some(playing) = self.playing.get(&id) {
+            playing.sink.set_volume(volume);
+        }
+    }
+
+    fn stop(&mut self, id: SoundId) {
+        if let Some(playing) = self.playing.remove(&id) {
+            playing.sink.stop();
+        }
+    }
+
+    fn is_playing(&self, id: SoundId) -> bool {
+        self.playing.contains_key(&id)
+    }
+
+    fn update(&mut self) {
+        let sources = &self.sources;
+
+        self.playing.retain(|_, playing| {
+            if !playing.sink.empty() {
+                return true;
+            } else if !playing.looping {
+                return false;
+            }
+
+            match sources.get(&playing.file).map(|source| playing.sink.append(source)) {
+                Some(Ok(())) => true,
+                _ => false,
+            }
+        });
+    }
+}
+
+struct Fade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Fade {
+    fn new(from: f32, to: f32, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    ///Advances by `delta` seconds and returns current gain.
+    fn update(&mut self, delta: f32) -> f32 {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+
+        if self.duration > 0.0 {
+            self.from + (self.to - self.from) * self.elapsed / self.duration
+        } else {
+            self.to
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+struct Sound {
+    id: SoundId,
+    file: String,
+    looping: bool,
+    ///Volume relative to channel.
+    level: f32,
+    gain: f32,
+    fade: Option<Fade>,
+    //Sound is removed once fade is finished
+    stopping: bool,
+}
+
+impl Sound {
+    fn fade_out(&mut self, duration: f32) {
+        self.fade = Some(Fade::new(self.gain, 0.0, duration));
+        self.stopping = true;
+    }
+}
+
+struct ChannelState {
+    volume: f32,
+    sounds: Vec<Sound>,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            volume: DEFAULT_VOLUME,
+            sounds: Vec::new(),
+        }
+    }
+}
+
+///Whether voice is stopped when dialogue advances to the next line.
+pub struct StopVoice(pub bool);
+
+impl Default for StopVoice {
+    fn default() -> Self {
+        StopVoice(true)
+    }
+}
+
+///Sounds that are being played on channels.
+pub struct Audio {
+    backend: Box<dyn Backend + Send + Sync>,
+    //Whether there is no sound device
+    silent: bool,
+    channels: HashMap<Channel, ChannelState>,
+    next_id: SoundId,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        let mut audio = Self::with_backend(Box::new(Null::default()));
+        audio.silent = true;
+        audio
+    }
+}
+
+impl Audio {
+    ///Creates audio that plays on `output` device, or without sound if there is none.
+    pub fn new(output: Option<amethyst::audio::output::Output>) -> Self {
+        match output {
+            Some(output) => Self::with_backend(Box::new(Device::new(output))),
+            None => {
+                warn!("No audio output device, game will be silent");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn with_backend(backend: Box<dyn Backend + Send + Sync>) -> Self {
+        Self {
+            backend,
+            silent: false,
+            channels: Channel::ALL.iter().map(|channel| (*channel, ChannelState::default())).collect(),
+            next_id: 0,
+        }
+    }
+
+    ///Returns whether sounds are not played, because there is no sound device.
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    fn channel_mut(&mut self, channel: Channel) -> &mut ChannelState {
+        match self.channels.get_mut(&channel) {
+            Some(state) => state,
+            None => unreach!(),
+        }
+    }
+
+    ///Plays `assets/audio/{file}` on channel.
+    ///
+    ///Unless channel is mixed, current sound is replaced.
+    ///With `fade` new sound fades in while old one fades out, so music is crossfaded.
+    pub fn play(&mut self, channel: Channel, file: &str, looping: bool, fade: Option<f32>) {
+        self.play_at(channel, file, looping, fade, 1.0)
+    }
+
+    ///Plays sound with volume `level` relative to channel's volume.
+    pub fn play_at(&mut self, channel: Channel, file: &str, looping: bool, fade: Option<f32>, level: f32) {
+        //Restarting the same music would be noticeable, so it just continues
+        let is_playing = self.channel_mut(channel).sounds.iter().any(|sound| !sound.stopping && sound.looping && sound.file == file);
+        if is_playing && !channel.is_mixed() {
+            return;
+        }
+
+        if !channel.is_mixed() {
+            self.stop(channel, fade);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let gain = match fade {
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+        let volume = self.channel_mut(channel).volume;
+        if !self.backend.play(id, file, looping, gain * level * volume) {
+            return;
+        }
+
+        self.channel_mut(channel).sounds.push(Sound {
+            id,
+            file: file.to_owned(),
+            looping,
+            level,
+            gain,
+            fade: fade.map(|duration| Fade::new(0.0, 1.0, duration)),
+            stopping: false,
+        });
+    }
+
+    ///Stops all sounds of channel, fading them out if `fade` duration is specified.
+    pub fn stop(&mut self, channel: Channel, fade: Option<f32>) {
+        let state = match self.channels.get_mut(&channel) {
+            Some(state) => state,
+            None => unreach!(),
+        };
+
+        match fade {
+            Some(duration) => for sound in state.sounds.iter_mut() {
+                sound.fade_out(duration);
+            },
+            None => for sound in state.sounds.drain(..) {
+                self.backend.stop(sound.id);
+            },
+        }
+    }
+
+    ///Stops all sounds on every channel.
+    pub fn stop_all(&mut self) {
+        for channel in Channel::ALL.iter() {
+            self.stop(*channel, None);
+        }
+    }
+
+    pub fn volume(&self, channel: Channel) -> f32 {
+        self.channels.get(&channel).map(|state| state.volume).unwrap_or(DEFAULT_VOLUME)
+    }
+
+    ///Sets volume of channel, from 0 to 1.
+    pub fn set_volume(&mut self, channel: Channel, volume: f32) {
+        let volume = volume.max(0.0).min(1.0);
+        let state = match self.channels.get_mut(&channel) {
+            Some(state) => state,
+            None => unreach!(),
+        };
+        state.volume = volume;
+
+        for sound in state.sounds.iter() {
+            self.backend.set_volume(sound.id, sound.gain * sound.level * volume);
+        }
+    }
+
+    ///Returns whether anything is playing on channel.
+    pub fn is_playing(&self, channel: Channel) -> bool {
+        self.channels.get(&channel).map(|state| state.sounds.iter().any(|sound| !sound.stopping)).unwrap_or(false)
+    }
+
+    ///Returns file of music that is currently playing.
+    pub fn bgm(&self) -> Option<&str> {
+        self.channels.get(&Channel::Bgm)
+                     .and_then(|state| state.sounds.iter().rev().find(|sound| !sound.stopping))
+                     .map(|sound| sound.file.as_str())
+    }
+
+    ///Advances fades by `delta` seconds and removes finished sounds.
+    pub fn update(&mut self, delta: f32) {
+        self.backend.update();
+
+        let backend = &mut self.backend;
+        for state in self.channels.values_mut() {
+            let volume = state.volume;
+
+            state.sounds.retain(|sound| backend.is_playing(sound.id));
+            for sound in state.sounds.iter_mut() {
+                if let Some(fade) = sound.fade.as_mut() {
+                    sound.gain = fade.update(delta);
+                    backend.set_volume(sound.id, sound.gain * sound.level * volume);
+                }
+
+                if sound.fade.as_ref().map(Fade::is_finished).unwrap_or(false) {
+                    sound.fade = None;
+                    if sound.stopping {
+                        backend.stop(sound.id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sounds(audio: &Audio, channel: Channel) -> usize {
+        audio.channels[&channel].sounds.len()
+    }
+
+    #[test]
+    fn fall_back_without_device() {
+        let mut audio = Audio::new(None);
+        assert!(audio.is_silent());
+
+        //Channels work the same, so script and saves don't depend on sound device
+        audio.play(Channel::Bgm, "evening.ogg", true, None);
+        audio.update(0.1);
+        assert_eq!(audio.bgm(), Some("evening.ogg"));
+
+        //Device is used whenever there is one
+        let output = amethyst::audio::output::default_output();
+        assert_eq!(Audio::new(output.clone()).is_silent(), output.is_none());
+    }
+
+    #[test]
+    fn replace_music() {
+        let mut audio = Audio::default();
+
+        audio.play(Channel::Bgm, "evening.ogg", true, None);
+        assert!(audio.is_playing(Channel::Bgm));
+        assert_eq!(audio.bgm(), Some("evening.ogg"));
+
+        //The same music just continues
+        audio.play(Channel::Bgm, "evening.ogg", true, None);
+        assert_eq!(sounds(&audio, Channel::Bgm), 1);
+
+        audio.play(Channel::Bgm, "night.ogg", true, None);
+        assert_eq!(audio.bgm(), Some("night.ogg"));
+        assert_eq!(sounds(&audio, Channel::Bgm), 1);
+
+        audio.stop(Channel::Bgm, None);
+        assert!(!audio.is_playing(Channel::Bgm));
+        assert_eq!(audio.bgm(), None);
+    }
+
+    #[test]
+    fn crossfade_music() {
+        let mut audio = Audio::default();
+
+        audio.play(Channel::Bgm, "evening.ogg", true, None);
+        audio.play(Channel::Bgm, "night.ogg", true, Some(1.0));
+        assert_eq!(audio.bgm(), Some("night.ogg"));
+        assert_eq!(sounds(&audio, Channel::Bgm), 2);
+
+        audio.update(0.5);
+        assert_eq!(sounds(&audio, Channel::Bgm), 2);
+
+        //Old music is stopped once faded out, and removed on the next update
+        audio.update(0.5);
+        audio.update(0.0);
+        assert_eq!(sounds(&audio, Channel::Bgm), 1);
+        assert_eq!(audio.bgm(), Some("night.ogg"));
+    }
+
+    #[test]
+    fn fade_out() {
+        let mut audio = Audio::default();
+
+        audio.play(Channel::Ambient, "rain.ogg", true, None);
+        audio.stop(Channel::Ambient, Some(1.0));
+        assert!(!audio.is_playing(Channel::Ambient));
+        assert_eq!(sounds(&audio, Channel::Ambient), 1);
+
+        audio.update(1.0);
+        audio.update(0.0);
+        assert_eq!(sounds(&audio, Channel::Ambient), 0);
+    }
+
+    #[test]
+    fn mixed_effects() {
+        let mut audio = Audio::default();
+
+        audio.play(Channel::Sfx, "door.wav", false, None);
+        audio.play(Channel::Sfx, "steps.wav", true, None);
+        assert_eq!(sounds(&audio, Channel::Sfx), 2);
+
+        //Null backend finishes sounds that are not looped right away
+        audio.update(0.0);
+        assert_eq!(sounds(&audio, Channel::Sfx), 1);
+        assert!(audio.is_playing(Channel::Sfx));
+
+        audio.stop_all();
+        assert!(!audio.is_playing(Channel::Sfx));
+    }
+
+    #[test]
+    fn clamp_volume() {
+        let mut audio = Audio::default();
+        assert_eq!(audio.volume(Channel::Voice), DEFAULT_VOLUME);
+
+        audio.set_volume(Channel::Voice, 1.5);
+        assert_eq!(audio.volume(Channel::Voice), 1.0);
+
+        audio.set_volume(Channel::Voice, -0.5);
+        assert_eq!(audio.volume(Channel::Voice), 0.0);
+    }
+}
@@ -1,17 +1,169 @@
+//!Stage camera and virtual resolution
+//!
+//!Stage and UI are laid out in virtual resolution of `WIDTH`×`HEIGHT`,
+//!which is scaled to fit window while keeping aspect ratio.
+//!Rest of window is covered by letterbox bars.
+
 use amethyst::prelude::{Builder, World};
 
-pub const WIDTH: f32 = 5000.0;
-pub const HEIGHT: f32 = 3000.0;
+use super::ui::Resources;
+
+///Virtual width of stage.
+pub const WIDTH: f32 = 1920.0;
+///Virtual height of stage.
+pub const HEIGHT: f32 = 1080.0;
+//Above UI, but below screen transition
+const BARS_Z: f32 = 900.0;
+
+///Mapping of virtual resolution onto window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    ///Screen pixels per virtual pixel.
+    pub scale: f32,
+    ///Size of letterbox bars on each side, in screen pixels.
+    pub bars: (f32, f32),
+}
+
+impl Viewport {
+    pub fn new(dimensions: (f32, f32)) -> Self {
+        let scale = (dimensions.0 / WIDTH).min(dimensions.1 / HEIGHT);
+        let scale = if scale > 0.0 { scale } else { 1.0 };
+
+        Self {
+            scale,
+            bars: (((dimensions.0 - WIDTH * scale) / 2.0).max(0.0), ((dimensions.1 - HEIGHT * scale) / 2.0).max(0.0)),
+        }
+    }
+
+    pub fn from_screen(screen: &amethyst::renderer::ScreenDimensions) -> Self {
+        Self::new((screen.width(), screen.height()))
+    }
+
+    ///Converts virtual pixels to screen pixels.
+    pub fn to_screen(&self, value: f32) -> f32 {
+        value * self.scale
+    }
+
+    ///Returns shift of UI element with `anchor`, so that it is placed within viewport instead of window.
+    pub fn anchor_offset(&self, anchor: amethyst::ui::Anchor) -> (f32, f32) {
+        use amethyst::ui::Anchor;
+
+        let x = match anchor {
+            Anchor::TopLeft | Anchor::MiddleLeft | Anchor::BottomLeft => self.bars.0,
+            Anchor::TopRight | Anchor::MiddleRight | Anchor::BottomRight => -self.bars.0,
+            Anchor::TopMiddle | Anchor::Middle | Anchor::BottomMiddle => 0.0,
+        };
+        let y = match anchor {
+            Anchor::TopLeft | Anchor::TopMiddle | Anchor::TopRight => -self.bars.1,
+            Anchor::BottomLeft | Anchor::BottomMiddle | Anchor::BottomRight => self.bars.1,
+            Anchor::MiddleLeft | Anchor::Middle | Anchor::MiddleRight => 0.0,
+        };
+
+        (x, y)
+    }
+
+    ///Maps position of cursor in window onto stage.
+    ///
+    ///Cursor position is in screen pixels from top left corner, while stage's origin is bottom left.
+    ///Returns `None` when cursor is over letterbox bars.
+    pub fn to_stage(&self, cursor: (f32, f32), dimensions: (f32, f32)) -> Option<(f32, f32)> {
+        let x = (cursor.0 - self.bars.0) / self.scale;
+        let y = (dimensions.1 - cursor.1 - self.bars.1) / self.scale;
+
+        if x < 0.0 || x > WIDTH || y < 0.0 || y > HEIGHT {
+            None
+        } else {
+            Some((x, y))
+        }
+    }
+
+    ///Returns camera's projection, which shows whole stage in the middle of window.
+    pub fn projection(&self) -> amethyst::renderer::Projection {
+        let (x, y) = (self.bars.0 / self.scale, self.bars.1 / self.scale);
+        amethyst::renderer::Projection::orthographic(-x, WIDTH + x, -y, HEIGHT + y)
+    }
+}
+
+///Base font size of UI text in virtual pixels.
+///
+///Actual font size is updated on screen resize.
+pub struct VirtualFont(pub f32);
+
+impl amethyst::ecs::Component for VirtualFont {
+    type Storage = amethyst::ecs::DenseVecStorage<Self>;
+}
+
+#[derive(Clone, Copy)]
+enum Bar {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Bar {
+    fn resize(self, transform: &mut amethyst::ui::UiTransform, dimensions: (f32, f32)) {
+        let viewport = Viewport::new(dimensions);
+        let (width, height) = viewport.bars;
+
+        match self {
+            Bar::Left | Bar::Right => {
+                transform.width = width;
+                transform.height = dimensions.1;
+                transform.local_x = match self {
+                    Bar::Left => width / 2.0,
+                    _ => -width / 2.0,
+                };
+            },
+            Bar::Top | Bar::Bottom => {
+                transform.width = dimensions.0;
+                transform.height = height;
+                transform.local_y = match self {
+                    Bar::Bottom => height / 2.0,
+                    _ => -height / 2.0,
+                };
+            },
+        }
+    }
+
+    fn create(self, world: &mut World, resources: &Resources) -> amethyst::ecs::Entity {
+        let (name, anchor) = match self {
+            Bar::Left => ("LetterboxLeft", amethyst::ui::Anchor::MiddleLeft),
+            Bar::Right => ("LetterboxRight", amethyst::ui::Anchor::MiddleRight),
+            Bar::Top => ("LetterboxTop", amethyst::ui::Anchor::TopMiddle),
+            Bar::Bottom => ("LetterboxBottom", amethyst::ui::Anchor::BottomMiddle),
+        };
+
+        let mut transform = amethyst::ui::UiTransform::new(
+            name.to_string(),
+            anchor,
+            0.0, 0.0, BARS_Z,
+            0.0, 0.0,
+            0
+        );
+        {
+            let screen = world.read_resource::<amethyst::renderer::ScreenDimensions>();
+            self.resize(&mut transform, (screen.width(), screen.height()));
+        }
+
+        world.create_entity()
+             .with(transform)
+             .with(amethyst::ui::UiImage { texture: resources.background.black.clone() })
+             .with(amethyst::ui::UiResize::new(move |transform: &mut amethyst::ui::UiTransform, dimensions: (f32, f32)| self.resize(transform, dimensions)))
+             .build()
+    }
+}
 
 pub struct Camera {
     inner: amethyst::ecs::Entity,
+    //Hide sprites that are outside of stage
+    bars: [amethyst::ecs::Entity; 4],
 }
 
 impl Camera {
     pub fn new(world: &mut World) -> Self {
-        //TODO: think about camera sizes
-        let camera = amethyst::renderer::Projection::orthographic(0.0, WIDTH, 0.0, HEIGHT);
-        let camera = amethyst::renderer::Camera::from(camera);
+        let viewport = Viewport::from_screen(&world.read_resource::<amethyst::renderer::ScreenDimensions>());
+        let camera = amethyst::renderer::Camera::from(viewport.projection());
         let mut transform = amethyst::core::Transform::default();
         transform.set_z(100.0);
 
@@ -20,13 +172,91 @@ impl Camera {
                          .with(transform)
                          .build();
 
+        let resources = Resources::fetch(world);
+        let bars = [
+            Bar::Left.create(world, &resources),
+            Bar::Right.create(world, &resources),
+            Bar::Top.create(world, &resources),
+            Bar::Bottom.create(world, &resources),
+        ];
 
         Self {
-            inner
+            inner,
+            bars,
         }
     }
 
     pub fn destroy(self, world: &mut World) {
         let _ = world.delete_entity(self.inner);
+        for bar in self.bars.iter() {
+            let _ = world.delete_entity(*bar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::ui::Anchor;
+
+    fn assert_close(left: (f32, f32), right: (f32, f32)) {
+        assert!((left.0 - right.0).abs() < 0.01 && (left.1 - right.1).abs() < 0.01, "{:?} != {:?}", left, right);
+    }
+
+    #[test]
+    fn fit_window() {
+        //16:9 is scaled without bars
+        let viewport = Viewport::new((1280.0, 720.0));
+        assert_close((viewport.scale, 0.0), (2.0 / 3.0, 0.0));
+        assert_close(viewport.bars, (0.0, 0.0));
+
+        //4:3 gets letterbox
+        let viewport = Viewport::new((1024.0, 768.0));
+        assert_close((viewport.scale, 0.0), (1024.0 / 1920.0, 0.0));
+        assert_close(viewport.bars, (0.0, 96.0));
+
+        //21:9 gets pillarbox
+        let viewport = Viewport::new((2560.0, 1080.0));
+        assert_close((viewport.scale, 0.0), (1.0, 0.0));
+        assert_close(viewport.bars, (320.0, 0.0));
+        assert_close((viewport.to_screen(100.0), 0.0), (100.0, 0.0));
+    }
+
+    #[test]
+    fn anchor_within_viewport() {
+        let viewport = Viewport::new((1024.0, 768.0));
+
+        assert_close(viewport.anchor_offset(Anchor::TopLeft), (0.0, -96.0));
+        assert_close(viewport.anchor_offset(Anchor::BottomMiddle), (0.0, 96.0));
+        assert_close(viewport.anchor_offset(Anchor::Middle), (0.0, 0.0));
+
+        let viewport = Viewport::new((2560.0, 1080.0));
+        assert_close(viewport.anchor_offset(Anchor::MiddleLeft), (320.0, 0.0));
+        assert_close(viewport.anchor_offset(Anchor::BottomRight), (-320.0, 0.0));
+    }
+
+    #[test]
+    fn map_cursor_to_stage() {
+        for dimensions in [(1280.0, 720.0), (1024.0, 768.0), (2560.0, 1080.0)].iter().cloned() {
+            let viewport = Viewport::new(dimensions);
+
+            for point in [(1.0, 1.0), (960.0, 540.0), (1919.0, 1079.0), (300.0, 800.0)].iter().cloned() {
+                let cursor = (viewport.bars.0 + viewport.to_screen(point.0), dimensions.1 - viewport.bars.1 - viewport.to_screen(point.1));
+                let stage = viewport.to_stage(cursor, dimensions).expect("To be on stage");
+                assert_close(stage, point);
+            }
+        }
+
+        //Clicks on bars are outside of stage
+        let dimensions = (1024.0, 768.0);
+        let viewport = Viewport::new(dimensions);
+        assert_eq!(viewport.to_stage((512.0, 10.0), dimensions), None);
+        assert_eq!(viewport.to_stage((512.0, 760.0), dimensions), None);
+        assert!(viewport.to_stage((512.0, 384.0), dimensions).is_some());
+
+        let dimensions = (2560.0, 1080.0);
+        let viewport = Viewport::new(dimensions);
+        assert_eq!(viewport.to_stage((100.0, 540.0), dimensions), None);
+        assert_eq!(viewport.to_stage((2500.0, 540.0), dimensions), None);
     }
 }
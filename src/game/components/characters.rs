@@ -0,0 +1,322 @@
+//!Character definitions
+//!
+//!Characters are described by RON manifest:
+//!
+//!```text
+//!(
+//!    characters: [
+//!        (
+//!            id: "kaoru",
+//!            name: "Kaoru",
+//!            color: (1.0, 0.6, 0.8, 1.0),
+//!            position: Some("center"),
+//!            voice_volume: 0.8,
+//!            expressions: {
+//!                "default": (sheet: "assets/sprites/BloodyChronicles/Kaoru1.png", index: 0),
+//!            },
+//!        ),
+//!    ],
+//!)
+//!```
+//!
+//!`id` is used by script, while `name` is shown on name plate.
+//!`position` is one of stage positions that script uses.
+//!`index` is number of sprite in sprite sheet.
+//!`voice_volume` is relative to volume of voice channel.
+
+use amethyst::prelude::World;
+use amethyst::renderer::SpriteSheetHandle;
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, fs, io};
+
+use crate::game::components::ui::DEFAULT_NAME_COLOR;
+use crate::game::graphics::{ImageFormat, Sprite, TextureLoader};
+use crate::game::script::StagePosition;
+
+///Manifest that is loaded on start.
+pub const MANIFEST: &'static str = "assets/characters.ron";
+///Expression that is used when script doesn't specify one.
+pub const DEFAULT_EXPRESSION: &'static str = "default";
+
+fn default_color() -> [f32; 4] {
+    DEFAULT_NAME_COLOR
+}
+
+fn default_voice_volume() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    characters: Vec<Definition>,
+}
+
+#[derive(Deserialize)]
+struct Definition {
+    id: String,
+    name: String,
+    #[serde(default = "default_color")]
+    color: [f32; 4],
+    #[serde(default)]
+    position: Option<String>,
+    #[serde(default = "default_voice_volume")]
+    voice_volume: f32,
+    expressions: HashMap<String, ExpressionDefinition>,
+}
+
+#[derive(Deserialize)]
+struct ExpressionDefinition {
+    ///Path to sprite image, sprite sheet is expected next to it with `.ron` extension.
+    sheet: String,
+    #[serde(default)]
+    index: usize,
+}
+
+///Sprite sheet as it is read by amethyst.
+#[derive(Deserialize)]
+struct SheetDefinition {
+    sprites: Vec<SpriteDefinition>,
+}
+
+//Sprite's position is read by amethyst, only number of sprites is needed here
+#[derive(Deserialize)]
+struct SpriteDefinition {}
+
+fn sprite_count(sheet: &Path) -> Result<usize, String> {
+    let data = fs::read_to_string(sheet).map_err(|error| error.to_string())?;
+    let sheet: SheetDefinition = ron::de::from_str(&data).map_err(|error| error.to_string())?;
+    Ok(sheet.sprites.len())
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ron::de::Error),
+    DuplicateCharacter(PathBuf, String),
+    MissingFile {
+        manifest: PathBuf,
+        character: String,
+        expression: String,
+        file: PathBuf,
+    },
+    UnknownFormat {
+        manifest: PathBuf,
+        character: String,
+        expression: String,
+        sheet: String,
+    },
+    UnknownPosition {
+        manifest: PathBuf,
+        character: String,
+        position: String,
+    },
+    InvalidSheet {
+        manifest: PathBuf,
+        character: String,
+        expression: String,
+        file: PathBuf,
+        error: String,
+    },
+    InvalidIndex {
+        manifest: PathBuf,
+        character: String,
+        expression: String,
+        index: usize,
+        count: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            Error::Parse(path, error) => write!(fmt, "{}: invalid manifest: {}", path.display(), error),
+            Error::DuplicateCharacter(path, id) => write!(fmt, "{}: character '{}' is defined more than once", path.display(), id),
+            Error::MissingFile { manifest, character, expression, file } => write!(fmt, "{}: character '{}' expression '{}': file {} doesn't exist", manifest.display(), character, expression, file.display()),
+            Error::UnknownFormat { manifest, character, expression, sheet } => write!(fmt, "{}: character '{}' expression '{}': unknown image format of {}, expected png or jpg", manifest.display(), character, expression, sheet),
+            Error::UnknownPosition { manifest, character, position } => write!(fmt, "{}: character '{}': unknown stage position '{}'", manifest.display(), character, position),
+            Error::InvalidSheet { manifest, character, expression, file, error } => write!(fmt, "{}: character '{}' expression '{}': invalid sprite sheet {}: {}", manifest.display(), character, expression, file.display(), error),
+            Error::InvalidIndex { manifest, character, expression, index, count } => write!(fmt, "{}: character '{}' expression '{}': sprite {} is out of range, sheet has {} sprites", manifest.display(), character, expression, index, count),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+///Sprite of character's expression.
+#[derive(Clone)]
+pub struct Expression {
+    pub sprite_sheet: SpriteSheetHandle,
+    pub sprite_number: usize,
+}
+
+pub struct Character {
+    ///Name shown on name plate.
+    pub name: String,
+    pub color: [f32; 4],
+    ///Position on stage, when script doesn't specify it.
+    pub position: Option<StagePosition>,
+    ///Volume of character's voice, from 0 to 1.
+    pub voice_volume: f32,
+    expressions: HashMap<String, Expression>,
+}
+
+impl Character {
+    ///Returns expression by its name, or default one.
+    pub fn expression(&self, name: Option<&str>) -> Option<&Expression> {
+        self.expressions.get(name.unwrap_or(DEFAULT_EXPRESSION))
+    }
+}
+
+///Registry of characters.
+#[derive(Clone, Default)]
+pub struct Characters {
+    inner: Arc<HashMap<String, Character>>,
+}
+
+impl Characters {
+    ///Loads characters from manifest.
+    ///
+    ///Expressions with missing files are reported and skipped,
+    ///while unknown image formats, positions and sprite indices make whole manifest invalid.
+    pub fn load(world: &mut World, manifest: &Path) -> Result<Self, Error> {
+        let data = fs::read_to_string(manifest).map_err(|error| Error::Io(manifest.to_path_buf(), error))?;
+        let definitions: Manifest = ron::de::from_str(&data).map_err(|error| Error::Parse(manifest.to_path_buf(), error))?;
+
+        //Sprite sheet can be shared by several expressions
+        let mut sheets = HashMap::<String, (SpriteSheetHandle, usize)>::new();
+        let mut characters = HashMap::with_capacity(definitions.characters.len());
+
+        for definition in definitions.characters {
+            if characters.contains_key(&definition.id) {
+                return Err(Error::DuplicateCharacter(manifest.to_path_buf(), definition.id));
+            }
+
+            let position = match definition.position {
+                Some(position) => match StagePosition::from_name(&position) {
+                    Some(position) => Some(position),
+                    None => return Err(Error::UnknownPosition {
+                        manifest: manifest.to_path_buf(),
+                        character: definition.id,
+                        position,
+                    }),
+                },
+                None => None,
+            };
+
+            let mut expressions = HashMap::with_capacity(definition.expressions.len());
+
+            for (name, expression) in definition.expressions {
+                if ImageFormat::from_path(&expression.sheet).is_none() {
+                    return Err(Error::UnknownFormat {
+                        manifest: manifest.to_path_buf(),
+                        character: definition.id,
+                        expression: name,
+                        sheet: expression.sheet,
+                    });
+                }
+
+                let image = PathBuf::from(&expression.sheet);
+                let sheet = image.with_extension("ron");
+
+                if let Some(file) = [&image, &sheet].iter().find(|file| !file.exists()) {
+                    error!("{}", Error::MissingFile {
+                        manifest: manifest.to_path_buf(),
+                        character: definition.id.clone(),
+                        expression: name,
+                        file: file.to_path_buf(),
+                    });
+                    continue;
+                }
+
+                let (sprite_sheet, count) = match sheets.get(&expression.sheet) {
+                    Some(loaded) => loaded.clone(),
+                    None => {
+                        let count = match sprite_count(&sheet) {
+                            Ok(count) => count,
+                            Err(error) => return Err(Error::InvalidSheet {
+                                manifest: manifest.to_path_buf(),
+                                character: definition.id,
+                                expression: name,
+                                file: sheet,
+                                error,
+                            }),
+                        };
+                        let sprite_sheet = match Sprite::File(expression.sheet.clone()).load(world) {
+                            Ok(sprite_sheet) => sprite_sheet,
+                            //Format is verified above
+                            Err(_) => unreach!(),
+                        };
+                        sheets.insert(expression.sheet, (sprite_sheet.clone(), count));
+                        (sprite_sheet, count)
+                    }
+                };
+
+                if expression.index >= count {
+                    return Err(Error::InvalidIndex {
+                        manifest: manifest.to_path_buf(),
+                        character: definition.id,
+                        expression: name,
+                        index: expression.index,
+                        count,
+                    });
+                }
+
+                expressions.insert(name, Expression {
+                    sprite_sheet,
+                    sprite_number: expression.index,
+                });
+            }
+
+            characters.insert(definition.id, Character {
+                name: definition.name,
+                color: definition.color,
+                position,
+                voice_volume: definition.voice_volume.max(0.0).min(1.0),
+                expressions,
+            });
+        }
+
+        Ok(Self {
+            inner: Arc::new(characters),
+        })
+    }
+
+    ///Returns registry, loading it from `MANIFEST` if it is not loaded yet.
+    ///
+    ///It is loaded by title screen on start, so that errors in manifest are reported right away.
+    pub fn fetch(world: &mut World) -> Self {
+        if !world.res.has_value::<Self>() {
+            let this = match Self::load(world, Path::new(MANIFEST)) {
+                Ok(this) => this,
+                Err(error) => {
+                    error!("Unable to load characters: {}", error);
+                    Self::default()
+                }
+            };
+            world.add_resource(this);
+        }
+
+        world.read_resource::<Self>().clone()
+    }
+
+    ///Returns character by its identifier in script.
+    pub fn get(&self, id: &str) -> Option<&Character> {
+        self.inner.get(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_sprites() {
+        assert_eq!(sprite_count(Path::new("assets/sprites/BloodyChronicles/Kaoru1.ron")), Ok(1));
+        assert!(sprite_count(Path::new("assets/characters.ron")).is_err());
+    }
+}
@@ -1,3 +1,8 @@
 pub mod ui;
-pub mod sprites;
+pub mod characters;
 pub mod camera;
+pub mod stage;
+pub mod typewriter;
+pub mod tween;
+pub mod transition;
+pub mod scope;
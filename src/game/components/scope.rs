@@ -0,0 +1,129 @@
+//!Entity ownership
+//!
+//!Every state enters its own scope on start and exits it on stop.
+//!Entities are claimed by the scope in which they are created and deleted when it is exited,
+//!so that nothing is left behind even if state forgets to delete some of its entities.
+
+use amethyst::core::transform::components::Parent;
+use amethyst::ecs::{Component, DenseVecStorage, NullStorage, Entity, Join};
+use amethyst::prelude::World;
+
+pub type ScopeId = u32;
+///Scope of entities that are created before any state is started.
+const ROOT: ScopeId = 0;
+
+///Scope that owns entity.
+pub struct Owner(pub ScopeId);
+
+impl Component for Owner {
+    type Storage = DenseVecStorage<Self>;
+}
+
+///Marks entity that outlives states, like overlay of screen transition.
+#[derive(Default)]
+pub struct Persistent;
+
+impl Component for Persistent {
+    type Storage = NullStorage<Self>;
+}
+
+///Stack of scopes, which follows stack of states.
+pub struct Scopes {
+    stack: Vec<ScopeId>,
+    next: ScopeId,
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Self {
+            stack: vec![ROOT],
+            next: ROOT + 1,
+        }
+    }
+}
+
+impl Scopes {
+    fn current(&self) -> ScopeId {
+        match self.stack.last() {
+            Some(scope) => *scope,
+            None => unreach!(),
+        }
+    }
+}
+
+///Assigns entities without owner to current scope.
+///
+///As it is done on entering and exiting scope, every entity without owner is created within current scope.
+fn claim(world: &mut World) {
+    let scope = world.read_resource::<Scopes>().current();
+    let entities = world.entities();
+    let mut owners = world.write_storage::<Owner>();
+    let persistent = world.read_storage::<Persistent>();
+
+    let unowned = (&*entities, !&owners, !&persistent).join().map(|(entity, _, _)| entity).collect::<Vec<_>>();
+    for entity in unowned {
+        owners.insert(entity, Owner(scope)).expect("To add Owner");
+    }
+}
+
+///Starts new scope, usually on start of state.
+pub fn enter(world: &mut World) {
+    claim(world);
+
+    let mut scopes = world.write_resource::<Scopes>();
+    let scope = scopes.next;
+    scopes.next += 1;
+    scopes.stack.push(scope);
+}
+
+///Deletes all entities of current scope and returns to the previous one.
+///
+///In debug build entities that are left by state are reported,
+///except children of deleted entities, which are expected to be deleted with their parents.
+pub fn exit(world: &mut World) {
+    claim(world);
+
+    let scope = {
+        let scopes = world.read_resource::<Scopes>();
+        if scopes.stack.len() <= 1 {
+            unreach!();
+        }
+        scopes.current()
+    };
+
+    let owned = {
+        let entities = world.entities();
+        let owners = world.read_storage::<Owner>();
+        (&*entities, &owners).join().filter(|(_, owner)| owner.0 == scope).map(|(entity, _)| entity).collect::<Vec<_>>()
+    };
+
+    if cfg!(debug_assertions) {
+        report_leaks(world, scope, &owned);
+    }
+
+    let _ = world.delete_entities(&owned);
+    world.write_resource::<Scopes>().stack.pop();
+}
+
+fn report_leaks(world: &World, scope: ScopeId, owned: &[Entity]) {
+    let entities = world.entities();
+    let parents = world.read_storage::<Parent>();
+    let transforms = world.read_storage::<amethyst::ui::UiTransform>();
+
+    let is_orphan = |mut entity: Entity| loop {
+        match parents.get(entity) {
+            Some(parent) if !entities.is_alive(parent.entity) => break true,
+            Some(parent) => entity = parent.entity,
+            None => break false,
+        }
+    };
+
+    let leaked = owned.iter().filter(|entity| !is_orphan(**entity)).map(|entity| match transforms.get(*entity) {
+        Some(transform) => transform.id.clone(),
+        None => format!("{:?}", entity),
+    }).collect::<Vec<_>>();
+
+    if !leaked.is_empty() {
+        warn!("Scope {}: {} entities are not deleted by state: {}", scope, leaked.len(), leaked.join(", "));
+    }
+}
@@ -0,0 +1,305 @@
+use amethyst::prelude::{Builder, World};
+use amethyst::ecs::{Component, DenseVecStorage};
+
+use std::collections::HashMap;
+
+use super::camera;
+use super::characters::Characters;
+use super::tween::{Tween, Easing};
+use crate::game::graphics::{Sprite, TextureLoader};
+use crate::game::script::{StagePosition, BackgroundFit, Motion};
+use crate::game::save;
+
+const BACKGROUND_DIR: &'static str = "assets/background";
+const BACKGROUND_Z: f32 = -10.0;
+const CHARACTER_Z: f32 = 0.0;
+///Height of character sprite relative to stage.
+const CHARACTER_HEIGHT: f32 = 0.9;
+
+///Scales sprite to the height once its sprite sheet is loaded.
+///
+///Component is removed after scale is set.
+pub struct FitHeight(pub f32);
+
+impl Component for FitHeight {
+    type Storage = DenseVecStorage<Self>;
+}
+
+///Scales sprite to the stage once its sprite sheet is loaded.
+///
+///Component is removed after scale is set.
+pub struct FitStage(pub BackgroundFit);
+
+impl Component for FitStage {
+    type Storage = DenseVecStorage<Self>;
+}
+
+struct Character {
+    entity: amethyst::ecs::Entity,
+    expression: Option<String>,
+    position: StagePosition,
+}
+
+struct Background {
+    entity: amethyst::ecs::Entity,
+    name: String,
+    fit: BackgroundFit,
+}
+
+///Entities visible on stage.
+pub struct Stage {
+    characters: HashMap<String, Character>,
+    background: Option<Background>,
+    //Entities that are being faded out, removed once their tween is finished
+    leaving: Vec<amethyst::ecs::Entity>,
+}
+
+///Starts to fade in entity over `duration` seconds.
+fn fade_in(world: &mut World, entity: amethyst::ecs::Entity, duration: f32) {
+    world.write_storage::<amethyst::renderer::Rgba>().insert(entity, amethyst::renderer::Rgba(1.0, 1.0, 1.0, 0.0)).expect("To add Rgba");
+    world.write_storage::<Tween>().insert(entity, Tween::new(duration, Easing::Linear).alpha(1.0)).expect("To add Tween");
+}
+
+impl Stage {
+    pub fn new() -> Self {
+        Self {
+            characters: HashMap::new(),
+            background: None,
+            leaving: Vec::new(),
+        }
+    }
+
+    ///Removes entity, fading it out first if `dissolve` duration is specified.
+    fn remove(&mut self, world: &mut World, entity: amethyst::ecs::Entity, dissolve: Option<f32>) {
+        match dissolve {
+            Some(duration) => {
+                world.write_storage::<Tween>().insert(entity, Tween::new(duration, Easing::Linear).alpha(0.0)).expect("To add Tween");
+                self.leaving.push(entity);
+            },
+            None => {
+                let _ = world.delete_entity(entity);
+            }
+        }
+    }
+
+    ///Removes entities that are faded out.
+    pub fn update(&mut self, world: &mut World) {
+        let tweens = world.read_storage::<Tween>();
+        let (faded, leaving) = self.leaving.drain(..).partition::<Vec<_>, _>(|entity| !tweens.contains(*entity));
+        self.leaving = leaving;
+        drop(tweens);
+
+        for entity in faded {
+            let _ = world.delete_entity(entity);
+        }
+    }
+
+    ///Shows character or changes its sprite, if character is already on stage.
+    ///
+    ///Without `position` character stays where it is, or appears at its default position.
+    ///With `dissolve` duration new sprite fades in, replacing old one.
+    pub fn show(&mut self, world: &mut World, characters: &Characters, name: &str, expression: Option<&str>, position: Option<StagePosition>, dissolve: Option<f32>) {
+        let (sprite, default_position) = match characters.get(name) {
+            Some(character) => match character.expression(expression) {
+                Some(sprite) => (sprite.clone(), character.position),
+                None => {
+                    warn!("Character '{}' has no expression '{}'", name, expression.unwrap_or(super::characters::DEFAULT_EXPRESSION));
+                    return;
+                }
+            },
+            None => {
+                warn!("Unknown character '{}'", name);
+                return;
+            }
+        };
+
+        let renderer = amethyst::renderer::SpriteRender {
+            sprite_sheet: sprite.sprite_sheet,
+            sprite_number: sprite.sprite_number,
+        };
+
+        if let Some(duration) = dissolve {
+            //Crossfade old sprite with the new one
+            let position = match self.characters.remove(name) {
+                Some(character) => {
+                    self.remove(world, character.entity, dissolve);
+                    position.unwrap_or(character.position)
+                },
+                None => position.or(default_position).unwrap_or_default(),
+            };
+
+            let entity = self.add(world, name, renderer, expression, position);
+            fade_in(world, entity, duration);
+            return;
+        }
+
+        let character = match self.characters.get_mut(name) {
+            Some(character) => character,
+            None => {
+                self.add(world, name, renderer, expression, position.or(default_position).unwrap_or_default());
+                return;
+            }
+        };
+
+        world.write_storage::<amethyst::renderer::SpriteRender>().insert(character.entity, renderer).expect("To update SpriteRender");
+        //New sprite can be of different size
+        world.write_storage::<FitHeight>().insert(character.entity, FitHeight(camera::HEIGHT * CHARACTER_HEIGHT)).expect("To add FitHeight");
+        character.expression = expression.map(str::to_owned);
+
+        if let Some(position) = position {
+            self.move_to(world, name, position, None);
+        }
+    }
+
+    fn add(&mut self, world: &mut World, name: &str, renderer: amethyst::renderer::SpriteRender, expression: Option<&str>, position: StagePosition) -> amethyst::ecs::Entity {
+        let height = camera::HEIGHT * CHARACTER_HEIGHT;
+
+        //Sprite is centered, so it is raised by half of its height to stand on bottom edge
+        let mut transform = amethyst::core::Transform::default();
+        transform.set_xyz(position.x(camera::WIDTH), height / 2.0, CHARACTER_Z);
+
+        let entity = world.create_entity()
+                          .with(renderer)
+                          .with(transform)
+                          .with(FitHeight(height))
+                          .build();
+
+        self.characters.insert(name.to_owned(), Character {
+            entity,
+            expression: expression.map(str::to_owned),
+            position,
+        });
+
+        entity
+    }
+
+    ///Moves character to another position.
+    ///
+    ///Returns entity of character if it is animated.
+    pub fn move_to(&mut self, world: &mut World, name: &str, position: StagePosition, motion: Option<Motion>) -> Option<amethyst::ecs::Entity> {
+        let character = match self.characters.get_mut(name) {
+            Some(character) => character,
+            None => {
+                warn!("Character '{}' is not on stage", name);
+                return None;
+            }
+        };
+
+        character.position = position;
+        let x = position.x(camera::WIDTH);
+
+        match motion {
+            Some(motion) => {
+                let (y, z) = match world.read_storage::<amethyst::core::Transform>().get(character.entity) {
+                    Some(transform) => (transform.translation().y, transform.translation().z),
+                    None => return None,
+                };
+
+                let tween = Tween::new(motion.duration, motion.easing).position(x, y, z);
+                world.write_storage::<Tween>().insert(character.entity, tween).expect("To add Tween");
+                Some(character.entity)
+            },
+            None => {
+                //Stop ongoing movement, so that it doesn't override new position
+                world.write_storage::<Tween>().remove(character.entity);
+                if let Some(transform) = world.write_storage::<amethyst::core::Transform>().get_mut(character.entity) {
+                    transform.set_x(x);
+                }
+                None
+            }
+        }
+    }
+
+    ///Removes character from stage.
+    pub fn hide(&mut self, world: &mut World, name: &str, dissolve: Option<f32>) {
+        match self.characters.remove(name) {
+            Some(character) => self.remove(world, character.entity, dissolve),
+            None => warn!("Character '{}' is not on stage", name),
+        }
+    }
+
+    ///Replaces background with image `assets/background/{name}.png`
+    ///
+    ///Without `name` background is removed.
+    pub fn background(&mut self, world: &mut World, name: Option<&str>, fit: BackgroundFit, dissolve: Option<f32>) {
+        if let Some(background) = self.background.take() {
+            //Keep old background behind the new one while it fades out
+            if let Some(transform) = world.write_storage::<amethyst::core::Transform>().get_mut(background.entity) {
+                transform.set_z(BACKGROUND_Z - 1.0);
+            }
+            self.remove(world, background.entity, dissolve);
+        }
+
+        let name = match name {
+            Some(name) => name,
+            None => return,
+        };
+
+        let sprite_sheet = match Sprite::File(format!("{}/{}.png", BACKGROUND_DIR, name)).load(world) {
+            Ok(sprite_sheet) => sprite_sheet,
+            Err(error) => {
+                error!("Unable to show background: {}", error);
+                return;
+            }
+        };
+        let renderer = amethyst::renderer::SpriteRender {
+            sprite_sheet,
+            sprite_number: 0,
+        };
+
+        let mut transform = amethyst::core::Transform::default();
+        transform.set_xyz(camera::WIDTH / 2.0, camera::HEIGHT / 2.0, BACKGROUND_Z);
+
+        let entity = world.create_entity()
+                          .with(renderer)
+                          .with(transform)
+                          .with(FitStage(fit))
+                          .build();
+
+        if let Some(duration) = dissolve {
+            fade_in(world, entity, duration);
+        }
+
+        self.background = Some(Background {
+            entity,
+            name: name.to_owned(),
+            fit,
+        });
+    }
+
+    ///Returns characters on stage, sorted by name.
+    pub fn characters(&self) -> Vec<save::Character> {
+        let mut result = self.characters.iter().map(|(name, character)| save::Character {
+            name: name.clone(),
+            expression: character.expression.clone(),
+            position: character.position,
+        }).collect::<Vec<_>>();
+
+        result.sort_by(|left, right| left.name.cmp(&right.name));
+        result
+    }
+
+    ///Returns name of current background.
+    pub fn background_name(&self) -> Option<&str> {
+        self.background.as_ref().map(|background| background.name.as_str())
+    }
+
+    ///Returns scaling of current background.
+    pub fn background_fit(&self) -> BackgroundFit {
+        self.background.as_ref().map(|background| background.fit).unwrap_or_default()
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        for (_, character) in self.characters {
+            let _ = world.delete_entity(character.entity);
+        }
+
+        if let Some(background) = self.background {
+            let _ = world.delete_entity(background.entity);
+        }
+
+        for entity in self.leaving {
+            let _ = world.delete_entity(entity);
+        }
+    }
+}
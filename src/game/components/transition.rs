@@ -0,0 +1,321 @@
+//!Screen transitions
+//!
+//!Screen is covered by overlay, which is drawn on top of everything including UI.
+//!State starts transition with `Transition::cover`, changes scene once `is_covered` and then calls `reveal`.
+
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::renderer::{Texture, TextureHandle};
+
+use std::collections::HashMap;
+
+use crate::game::graphics::Mask;
+use crate::game::script::Script;
+
+///Default duration of transition in seconds.
+pub const DEFAULT_DURATION: f32 = 0.5;
+pub const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+///Default width of gradient between covered and uncovered parts of rule transition.
+pub const DEFAULT_SOFTNESS: f32 = 0.1;
+const RULE_DIR: &'static str = "assets/rules";
+///Number of steps between transparent and opaque overlay.
+pub const STEPS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    ///Fades to colour and back.
+    Fade([f32; 4]),
+    ///Crossfade between old and new scene.
+    ///
+    ///Only stage can crossfade its sprites, so for the whole screen it is the same as fade to black.
+    Dissolve,
+    ///Colour panel slides over screen in direction and then away from it.
+    Wipe(Direction, [f32; 4]),
+    ///Screen is covered and then revealed in order of mask luminance, dark parts first.
+    Rule {
+        ///Name of grayscale image `assets/rules/{mask}.png`
+        mask: String,
+        ///Width of gradient at the edge of covered part, from 0 to 1.
+        softness: f32,
+    },
+}
+
+impl Kind {
+    ///Returns transition by its name in script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fade" => Some(Kind::Fade(BLACK)),
+            "fade-white" => Some(Kind::Fade([1.0, 1.0, 1.0, 1.0])),
+            "dissolve" => Some(Kind::Dissolve),
+            "wipe-left" => Some(Kind::Wipe(Direction::Left, BLACK)),
+            "wipe-right" => Some(Kind::Wipe(Direction::Right, BLACK)),
+            "wipe-up" => Some(Kind::Wipe(Direction::Up, BLACK)),
+            "wipe-down" => Some(Kind::Wipe(Direction::Down, BLACK)),
+            _ => None,
+        }
+    }
+
+    ///Creates rule transition with mask `name`.
+    pub fn rule(name: &str, softness: f32) -> Self {
+        Kind::Rule {
+            mask: name.to_owned(),
+            softness: softness.max(0.0).min(1.0),
+        }
+    }
+
+    ///Returns colour of overlay.
+    pub fn color(&self) -> [f32; 4] {
+        match self {
+            Kind::Fade(color) => *color,
+            Kind::Dissolve => BLACK,
+            Kind::Wipe(_, color) => *color,
+            Kind::Rule { .. } => BLACK,
+        }
+    }
+
+    ///Returns path to mask of rule transition.
+    pub fn mask_path(&self) -> Option<String> {
+        match self {
+            Kind::Rule { mask, .. } => Some(format!("{}/{}.png", RULE_DIR, mask)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Cover,
+    Covered,
+    Reveal,
+}
+
+///Screen transition that is currently in progress.
+///
+///Stored as resource and drawn by transition system.
+pub struct Transition {
+    kind: Kind,
+    phase: Phase,
+    duration: f32,
+    elapsed: f32,
+    ///Overlay entity, managed by transition system.
+    pub overlay: Option<amethyst::ecs::Entity>,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self {
+            kind: Kind::Fade(BLACK),
+            phase: Phase::Idle,
+            duration: 0.0,
+            elapsed: 0.0,
+            overlay: None,
+        }
+    }
+}
+
+impl Transition {
+    ///Starts covering screen.
+    pub fn cover(&mut self, kind: Kind, duration: f32) {
+        self.kind = kind;
+        self.phase = Phase::Cover;
+        self.duration = duration;
+        self.elapsed = 0.0;
+    }
+
+    ///Starts revealing covered screen.
+    ///
+    ///Does nothing if screen is not covered.
+    pub fn reveal(&mut self, duration: f32) {
+        if self.phase != Phase::Covered {
+            return;
+        }
+
+        self.phase = Phase::Reveal;
+        self.duration = duration;
+        self.elapsed = 0.0;
+    }
+
+    ///Returns whether screen is fully covered and waits to be revealed.
+    pub fn is_covered(&self) -> bool {
+        self.phase == Phase::Covered
+    }
+
+    ///Returns whether screen is being revealed.
+    pub fn is_revealing(&self) -> bool {
+        self.phase == Phase::Reveal
+    }
+
+    ///Returns whether overlay is visible.
+    pub fn is_active(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    ///Jumps to the end of current phase.
+    pub fn finish(&mut self) {
+        self.elapsed = self.duration;
+    }
+
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    ///Advances by `delta` seconds.
+    pub fn update(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+
+        if self.elapsed < self.duration {
+            return;
+        }
+
+        self.phase = match self.phase {
+            Phase::Cover => Phase::Covered,
+            Phase::Reveal => Phase::Idle,
+            phase => phase,
+        };
+    }
+
+    ///Returns progress of current phase, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        match self.phase {
+            Phase::Idle => 0.0,
+            Phase::Covered => 1.0,
+            _ => if self.duration > 0.0 {
+                self.elapsed / self.duration
+            } else {
+                1.0
+            },
+        }
+    }
+
+    ///Returns part of screen that is covered, from 0 to 1.
+    pub fn coverage(&self) -> f32 {
+        let progress = self.progress();
+
+        match self.phase {
+            Phase::Idle => 0.0,
+            Phase::Cover => progress,
+            Phase::Covered => 1.0,
+            Phase::Reveal => 1.0 - progress,
+        }
+    }
+}
+
+///Overlay textures of rule transition for each step of covering and revealing.
+pub struct Rule {
+    covering: Vec<TextureHandle>,
+    revealing: Vec<TextureHandle>,
+}
+
+impl Rule {
+    fn new(mask: &Mask, softness: f32, color: [f32; 4], loader: &Loader, storage: &AssetStorage<Texture>) -> Self {
+        let texture = |step: usize, revealing: bool| {
+            //Threshold goes a bit further than 1, so that the brightest pixels are fully covered too
+            let threshold = step as f32 / STEPS as f32 * (1.0 + softness);
+            let to_byte = |value: f32| (value * 255.0).round() as u8;
+
+            let mut data = Vec::with_capacity(mask.luminance.len() * 4);
+            for luminance in mask.luminance.iter() {
+                let luminance = *luminance as f32 / 255.0;
+                let covered = if softness > 0.0 {
+                    ((threshold - luminance) / softness).max(0.0).min(1.0)
+                } else if luminance < threshold {
+                    1.0
+                } else {
+                    0.0
+                };
+                let alpha = if revealing { 1.0 - covered } else { covered };
+
+                data.extend_from_slice(&[to_byte(color[0]), to_byte(color[1]), to_byte(color[2]), to_byte(color[3] * alpha)]);
+            }
+
+            let metadata = amethyst::renderer::TextureMetadata::srgb().with_size(mask.width as u16, mask.height as u16);
+            loader.load_from_data(amethyst::renderer::TextureData::U8(data, metadata), (), storage)
+        };
+
+        Self {
+            covering: (0..=STEPS).map(|step| texture(step, false)).collect(),
+            revealing: (0..=STEPS).map(|step| texture(step, true)).collect(),
+        }
+    }
+
+    ///Returns texture at `step` of transition.
+    ///
+    ///Pixel is covered once step passes its luminance, and then uncovered in the same order.
+    pub fn texture(&self, step: usize, revealing: bool) -> TextureHandle {
+        let textures = if revealing { &self.revealing } else { &self.covering };
+        textures[step.min(STEPS)].clone()
+    }
+}
+
+///Rule transitions of current script.
+///
+///Masks are decoded and their textures are generated when script is loaded,
+///so that transition system only picks texture of current step.
+#[derive(Default)]
+pub struct Rules {
+    //By mask name and bits of softness
+    inner: HashMap<(String, u32), Rule>,
+}
+
+impl Rules {
+    ///Prepares every rule transition used by script.
+    ///
+    ///Rules that are not used anymore are dropped.
+    ///Masks that cannot be loaded are reported, and such transitions fall back to fade.
+    pub fn prepare(&mut self, script: &Script, loader: &Loader, storage: &AssetStorage<Texture>) {
+        let mut old = std::mem::replace(&mut self.inner, HashMap::new());
+        //Mask can be used with different softness
+        let mut masks = HashMap::<String, Option<Mask>>::new();
+
+        for statement in script.statements.iter() {
+            let kind = match statement.command.transition() {
+                Some(transition) => transition.kind,
+                None => continue,
+            };
+            let (name, softness) = match kind {
+                Kind::Rule { ref mask, softness } => (mask.clone(), softness),
+                _ => continue,
+            };
+
+            let key = (name, softness.to_bits());
+            if self.inner.contains_key(&key) {
+                continue;
+            } else if let Some(rule) = old.remove(&key) {
+                self.inner.insert(key, rule);
+                continue;
+            }
+
+            let path = match kind.mask_path() {
+                Some(path) => path,
+                None => unreach!(),
+            };
+            if !masks.contains_key(&path) {
+                let mask = match Mask::load(&path) {
+                    Ok(mask) => Some(mask),
+                    Err(error) => {
+                        warn!("{}:{}: unable to load rule mask {}: {}", script.name, statement.line, path, error);
+                        None
+                    }
+                };
+                masks.insert(path.clone(), mask);
+            }
+
+            if let Some(Some(mask)) = masks.get(&path) {
+                let rule = Rule::new(mask, softness, kind.color(), loader, storage);
+                self.inner.insert(key, rule);
+            }
+        }
+    }
+
+    pub fn get(&self, mask: &str, softness: f32) -> Option<&Rule> {
+        self.inner.get(&(mask.to_owned(), softness.to_bits()))
+    }
+}
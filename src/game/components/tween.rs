@@ -0,0 +1,273 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+
+use std::f32::consts::PI;
+
+///Easing curve of tween.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    Bounce,
+    Elastic,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+impl Easing {
+    ///Returns easing by its name in script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "ease-in" => Some(Easing::EaseIn),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" => Some(Easing::EaseInOut),
+            "bounce" => Some(Easing::Bounce),
+            "elastic" => Some(Easing::Elastic),
+            _ => None,
+        }
+    }
+
+    ///Maps progress in range `[0, 1]` onto curve.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+            },
+            Easing::Bounce => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984_375
+                }
+            },
+            Easing::Elastic => if t <= 0.0 {
+                0.0
+            } else if t >= 1.0 {
+                1.0
+            } else {
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            },
+        }
+    }
+}
+
+#[inline]
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+///Animated values of entity.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct State {
+    pub position: [f32; 3],
+    pub scale: [f32; 3],
+    ///Rotation around z axis in radians.
+    pub rotation: f32,
+    pub alpha: f32,
+}
+
+///Interpolates entity's `Transform` and sprite alpha over time.
+///
+///Start values are taken from entity on first update.
+pub struct Tween {
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+    from: Option<State>,
+    position: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+    rotation: Option<f32>,
+    alpha: Option<f32>,
+}
+
+impl Component for Tween {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Tween {
+    pub fn new(duration: f32, easing: Easing) -> Self {
+        Self {
+            duration,
+            elapsed: 0.0,
+            easing,
+            from: None,
+            position: None,
+            scale: None,
+            rotation: None,
+            alpha: None,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.position = Some([x, y, z]);
+        self
+    }
+
+    pub fn scale(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.scale = Some([x, y, z]);
+        self
+    }
+
+    pub fn rotation(mut self, angle: f32) -> Self {
+        self.rotation = Some(angle);
+        self
+    }
+
+    pub fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    ///Returns whether alpha is animated.
+    pub fn has_alpha(&self) -> bool {
+        self.alpha.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    ///Jumps to the end of tween.
+    pub fn finish(&mut self) {
+        self.elapsed = self.duration;
+    }
+
+    ///Advances by `delta` seconds and returns values of `current` state at this moment.
+    pub fn update(&mut self, delta: f32, current: State) -> State {
+        let from = *self.from.get_or_insert(current);
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+
+        let t = if self.duration > 0.0 {
+            self.easing.apply(self.elapsed / self.duration)
+        } else {
+            1.0
+        };
+
+        let mut result = current;
+
+        if let Some(to) = self.position {
+            for idx in 0..3 {
+                result.position[idx] = lerp(from.position[idx], to[idx], t);
+            }
+        }
+
+        if let Some(to) = self.scale {
+            for idx in 0..3 {
+                result.scale[idx] = lerp(from.scale[idx], to[idx], t);
+            }
+        }
+
+        if let Some(to) = self.rotation {
+            result.rotation = lerp(from.rotation, to, t);
+        }
+
+        if let Some(to) = self.alpha {
+            result.alpha = lerp(from.alpha, to, t);
+        }
+
+        result
+    }
+}
+
+///Emitted by tween system into `EventChannel<TweenEvent>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TweenEvent {
+    ///Tween of entity is finished and removed.
+    Finished(Entity),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn assert_close(left: f32, right: f32) {
+        assert!((left - right).abs() < EPSILON, "{} != {}", left, right);
+    }
+
+    #[test]
+    fn easing_bounds() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::Bounce, Easing::Elastic].iter() {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(1.0), 1.0);
+        }
+
+        assert_close(Easing::EaseIn.apply(0.5), 0.25);
+        assert_close(Easing::EaseOut.apply(0.5), 0.75);
+        assert_close(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn step_to_completion() {
+        let start = State {
+            position: [0.0, 10.0, 1.0],
+            scale: [1.0, 1.0, 1.0],
+            rotation: 0.0,
+            alpha: 1.0,
+        };
+        let mut tween = Tween::new(1.0, Easing::Linear).position(100.0, 10.0, 1.0).alpha(0.0);
+
+        let state = tween.update(0.25, start);
+        assert_close(state.position[0], 25.0);
+        assert_close(state.position[1], 10.0);
+        assert_close(state.alpha, 0.75);
+        //Values that are not animated are kept
+        assert_eq!(state.scale, start.scale);
+        assert!(!tween.is_finished());
+
+        //Start values are taken only on first update
+        let state = tween.update(0.5, state);
+        assert_close(state.position[0], 75.0);
+        assert_close(state.alpha, 0.25);
+
+        let state = tween.update(0.5, state);
+        assert_close(state.position[0], 100.0);
+        assert_close(state.alpha, 0.0);
+        assert!(tween.is_finished());
+    }
+
+    #[test]
+    fn finish_early() {
+        let mut tween = Tween::new(2.0, Easing::EaseInOut).scale(2.0, 2.0, 1.0).rotation(1.0);
+        let state = tween.update(0.1, State::default());
+        assert!(!tween.is_finished());
+
+        tween.finish();
+        assert!(tween.is_finished());
+
+        let state = tween.update(0.0, state);
+        assert_eq!(state.scale, [2.0, 2.0, 1.0]);
+        assert_close(state.rotation, 1.0);
+    }
+
+    #[test]
+    fn zero_duration() {
+        let mut tween = Tween::new(0.0, Easing::Linear).position(5.0, 5.0, 0.0);
+        assert!(tween.is_finished());
+
+        let state = tween.update(0.0, State::default());
+        assert_eq!(state.position, [5.0, 5.0, 0.0]);
+    }
+}
@@ -0,0 +1,212 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+
+///Default number of characters revealed per second.
+pub const DEFAULT_SPEED: f32 = 40.0;
+///Additional delay after end of sentence, in seconds.
+const SENTENCE_PAUSE: f32 = 0.3;
+///Default delay of auto mode per character, in seconds.
+pub const DEFAULT_AUTO_DELAY: f32 = 0.05;
+///Delay of auto mode that is added regardless of line length, in seconds.
+const AUTO_BASE_DELAY: f32 = 1.0;
+
+///Text speed in characters per second.
+///
+///Non-positive value means text is shown at once.
+pub struct TextSpeed(pub f32);
+
+impl Default for TextSpeed {
+    fn default() -> Self {
+        TextSpeed(DEFAULT_SPEED)
+    }
+}
+
+///Delay of auto mode per character of line, in seconds.
+pub struct AutoDelay(pub f32);
+
+impl AutoDelay {
+    ///Returns time to wait after line with `len` characters is revealed.
+    pub fn delay(&self, len: usize) -> f32 {
+        AUTO_BASE_DELAY + self.0 * len as f32
+    }
+}
+
+impl Default for AutoDelay {
+    fn default() -> Self {
+        AutoDelay(DEFAULT_AUTO_DELAY)
+    }
+}
+
+#[inline]
+fn is_sentence_end(ch: char) -> bool {
+    match ch {
+        '.' | '!' | '?' | '…' => true,
+        _ => false,
+    }
+}
+
+///Reveals text of `UiText` character by character.
+pub struct Typewriter {
+    text: Vec<char>,
+    revealed: usize,
+    timer: f32,
+    //Overrides TextSpeed for the line
+    speed: Option<f32>,
+}
+
+impl Component for Typewriter {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Typewriter {
+    pub fn new(text: &str, speed: Option<f32>) -> Self {
+        Self {
+            text: text.chars().collect(),
+            revealed: 0,
+            timer: 0.0,
+            speed,
+        }
+    }
+
+    ///Returns whether whole text is revealed.
+    pub fn is_finished(&self) -> bool {
+        self.revealed >= self.text.len()
+    }
+
+    ///Reveals whole text at once.
+    pub fn complete(&mut self) {
+        self.revealed = self.text.len();
+    }
+
+    ///Returns revealed part of text.
+    pub fn visible(&self) -> String {
+        self.text[..self.revealed].iter().collect()
+    }
+
+    ///Returns delay before revealing next character.
+    fn next_delay(&self, speed: f32) -> f32 {
+        let delay = 1.0 / speed;
+        let prev = match self.revealed {
+            0 => None,
+            revealed => self.text.get(revealed - 1),
+        };
+
+        match (prev, self.text.get(self.revealed)) {
+            //Pause only once after sequence like `...` or `?!`
+            (Some(prev), Some(next)) if is_sentence_end(*prev) && !is_sentence_end(*next) => delay + SENTENCE_PAUSE,
+            _ => delay,
+        }
+    }
+
+    ///Advances by `delta` seconds.
+    ///
+    ///Returns whether more text is revealed.
+    pub fn update(&mut self, delta: f32, default_speed: f32) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        let speed = self.speed.unwrap_or(default_speed);
+        if speed <= 0.0 {
+            self.complete();
+            return true;
+        }
+
+        let revealed = self.revealed;
+        self.timer += delta;
+
+        while !self.is_finished() {
+            let delay = self.next_delay(speed);
+            if self.timer < delay {
+                break;
+            }
+
+            self.timer -= delay;
+            self.revealed += 1;
+        }
+
+        revealed != self.revealed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Four characters per second, so that time steps are exact
+    const SPEED: f32 = 4.0;
+    const STEP: f32 = 0.25;
+
+    fn advance(typewriter: &mut Typewriter, steps: usize) {
+        for _ in 0..steps {
+            typewriter.update(STEP, SPEED);
+        }
+    }
+
+    #[test]
+    fn pause_after_sentence() {
+        let mut typewriter = Typewriter::new("Hi. Yo", None);
+
+        advance(&mut typewriter, 3);
+        assert_eq!(typewriter.visible(), "Hi.");
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Hi.");
+        advance(&mut typewriter, 1);
+        assert_eq!(typewriter.visible(), "Hi. ");
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Hi. Yo");
+        assert!(typewriter.is_finished());
+    }
+
+    #[test]
+    fn pause_once_after_sequence() {
+        let mut typewriter = Typewriter::new("Wait... what?!", None);
+
+        advance(&mut typewriter, 5);
+        assert_eq!(typewriter.visible(), "Wait.");
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Wait...");
+        advance(&mut typewriter, 1);
+        assert_eq!(typewriter.visible(), "Wait...");
+    }
+
+    #[test]
+    fn no_pause_after_comma() {
+        let mut typewriter = Typewriter::new("Hi, Yo", None);
+
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Hi");
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Hi, ");
+        advance(&mut typewriter, 2);
+        assert_eq!(typewriter.visible(), "Hi, Yo");
+    }
+
+    #[test]
+    fn complete_line() {
+        let mut typewriter = Typewriter::new("Hello. World", None);
+
+        advance(&mut typewriter, 1);
+        assert!(!typewriter.is_finished());
+
+        typewriter.complete();
+        assert!(typewriter.is_finished());
+        assert_eq!(typewriter.visible(), "Hello. World");
+        assert!(!typewriter.update(STEP, SPEED));
+    }
+
+    #[test]
+    fn override_speed() {
+        let mut typewriter = Typewriter::new("Hello", None);
+        advance(&mut typewriter, 1);
+        assert_eq!(typewriter.visible(), "H");
+
+        let mut typewriter = Typewriter::new("Hello", Some(SPEED * 2.0));
+        advance(&mut typewriter, 1);
+        assert_eq!(typewriter.visible(), "He");
+
+        //Non-positive speed shows line at once
+        let mut typewriter = Typewriter::new("Hello", Some(0.0));
+        assert!(typewriter.update(0.0, SPEED));
+        assert_eq!(typewriter.visible(), "Hello");
+    }
+}
@@ -0,0 +1,54 @@
+use amethyst::prelude::World;
+
+use super::{layout, Layout, Resources};
+use crate::game::script;
+
+///Number of history entries shown at once.
+pub const VISIBLE_ENTRIES: usize = 8;
+
+///Dialogue history screen, described by `assets/ui/backlog.ron`.
+pub struct Backlog {
+    layout: Layout,
+    text: amethyst::ecs::Entity,
+}
+
+impl Backlog {
+    pub fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error> {
+        let layout = Layout::load(world, resources, "backlog")?;
+        Self::from_layout(world, layout)
+    }
+
+    ///Creates screen from builtin layout, in case layout file cannot be used.
+    pub fn builtin(world: &mut World, resources: &Resources) -> Self {
+        Layout::builtin(world, resources, "backlog").and_then(|layout| Self::from_layout(world, layout))
+                                                    .expect("To create builtin backlog screen")
+    }
+
+    fn from_layout(world: &mut World, layout: Layout) -> Result<Self, layout::Error> {
+        let (layout, text) = layout.bind(world, |layout| layout.entity("text"))?;
+
+        Ok(Self {
+            layout,
+            text,
+        })
+    }
+
+    ///Shows history entries.
+    ///
+    ///`offset` is number of newest entries that are scrolled out of view.
+    pub fn refresh(&self, world: &mut World, history: &script::History, offset: usize) {
+        let entries = history.iter().rev()
+                                    .skip(offset)
+                                    .take(VISIBLE_ENTRIES)
+                                    .map(script::HistoryEntry::display)
+                                    .collect::<Vec<_>>();
+
+        if let Some(text) = world.write_storage::<amethyst::ui::UiText>().get_mut(self.text) {
+            text.text = entries.iter().rev().map(String::as_str).collect::<Vec<_>>().join("\n\n");
+        }
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        self.layout.destroy(world);
+    }
+}
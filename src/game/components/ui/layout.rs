@@ -0,0 +1,601 @@
+//!Declarative UI layouts
+//!
+//!Screens are described in `assets/ui/{name}.ron` as tree of nodes:
+//!
+//!```ron
+//!(
+//!    nodes: [
+//!        (
+//!            id: "background",
+//!            image: Some(Menu),
+//!            stretch: XY(0.0, 0.0),
+//!        ),
+//!        (
+//!            id: "new_game",
+//!            kind: Button(text: "Start"),
+//!            y: Px(50.0),
+//!            z: 5.0,
+//!            width: Percent(20.0),
+//!            height: Px(100.0),
+//!            tab_order: 1,
+//!        ),
+//!    ],
+//!)
+//!```
+//!
+//!Positions, sizes and fonts are in pixels of virtual resolution, or in percents of it,
+//!and are scaled to window on resize.
+//!Top level nodes are anchored to the stage's viewport, so they stay out of letterbox bars.
+//!Children are positioned relative to their parent's anchor.
+//!
+//!Loaded layout allows to look up entities by node's `id`, so that states can react on UI events
+//!using names from layout file.
+//!
+//!Layouts shipped with the game are also built into executable,
+//!so that screen can be shown even if its file is broken.
+
+use amethyst::prelude::{Builder, World};
+use serde::Deserialize;
+
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+use super::{builder, Resources};
+use crate::game::components::camera::{self, Viewport, VirtualFont};
+
+const LAYOUT_DIR: &'static str = "assets/ui";
+const DEFAULT_FONT_SIZE: f32 = 42.0;
+const DEFAULT_BUTTON_FONT_SIZE: f32 = 28.0;
+const BUILTIN: [(&'static str, &'static str); 6] = [
+    ("menu", include_str!("../../../../assets/ui/menu.ron")),
+    ("adv", include_str!("../../../../assets/ui/adv.ron")),
+    ("pause", include_str!("../../../../assets/ui/pause.ron")),
+    ("settings", include_str!("../../../../assets/ui/settings.ron")),
+    ("slots", include_str!("../../../../assets/ui/slots.ron")),
+    ("backlog", include_str!("../../../../assets/ui/backlog.ron")),
+];
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ron::de::Error),
+    DuplicateNode(PathBuf, String),
+    MissingNode(PathBuf, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            Error::Parse(path, error) => write!(fmt, "{}: invalid layout: {}", path.display(), error),
+            Error::DuplicateNode(path, id) => write!(fmt, "{}: node '{}' is defined more than once", path.display(), id),
+            Error::MissingNode(path, id) => write!(fmt, "{}: layout has no node '{}'", path.display(), id),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+///Distance on screen.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum Dim {
+    ///Pixels of virtual resolution.
+    Px(f32),
+    ///Percent of virtual width or height.
+    Percent(f32),
+}
+
+impl Default for Dim {
+    fn default() -> Self {
+        Dim::Px(0.0)
+    }
+}
+
+impl Dim {
+    ///Returns value in virtual pixels.
+    fn resolve(self, size: f32) -> f32 {
+        match self {
+            Dim::Px(value) => value,
+            Dim::Percent(value) => size * value / 100.0,
+        }
+    }
+}
+
+///Builtin images from `Resources`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Image {
+    Menu,
+    Overlay,
+    TextWindow,
+    ///Menu button, which has hover and press images.
+    Button,
+    SmallButton,
+}
+
+impl Image {
+    fn texture(self, resources: &Resources) -> amethyst::renderer::TextureHandle {
+        match self {
+            Image::Menu => resources.background.menu.clone(),
+            Image::Overlay => resources.background.overlay.clone(),
+            Image::TextWindow => resources.adv.text_background.clone(),
+            Image::Button => resources.background.menu_button.clone(),
+            Image::SmallButton => resources.adv.close_background.clone(),
+        }
+    }
+}
+
+///Stretch of node within its parent, with margins in pixels.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum Stretch {
+    None,
+    X(f32),
+    Y(f32),
+    XY(f32, f32),
+}
+
+impl Default for Stretch {
+    fn default() -> Self {
+        Stretch::None
+    }
+}
+
+impl Stretch {
+    ///Returns stretch in screen pixels, with margins extended by `bars`.
+    fn to_screen(self, viewport: &Viewport, bars: (f32, f32)) -> amethyst::ui::Stretch {
+        let x_margin = |margin: f32| viewport.to_screen(margin) + bars.0;
+        let y_margin = |margin: f32| viewport.to_screen(margin) + bars.1;
+
+        match self {
+            Stretch::None => amethyst::ui::Stretch::NoStretch,
+            Stretch::X(x) => amethyst::ui::Stretch::X { x_margin: x_margin(x) },
+            Stretch::Y(y) => amethyst::ui::Stretch::Y { y_margin: y_margin(y) },
+            Stretch::XY(x, y) => amethyst::ui::Stretch::XY { x_margin: x_margin(x), y_margin: y_margin(y) },
+        }
+    }
+}
+
+fn default_anchor() -> amethyst::ui::Anchor {
+    amethyst::ui::Anchor::Middle
+}
+
+fn default_font_size() -> f32 {
+    DEFAULT_FONT_SIZE
+}
+
+fn default_button_font_size() -> f32 {
+    DEFAULT_BUTTON_FONT_SIZE
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Text {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+    ///Wraps text into multiple lines instead of cutting it.
+    #[serde(default)]
+    pub wrap: bool,
+    #[serde(default = "default_anchor")]
+    pub align: amethyst::ui::Anchor,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum Kind {
+    ///Node with optional image and text.
+    Panel,
+    Button {
+        text: String,
+        #[serde(default = "default_button_font_size")]
+        font_size: f32,
+        #[serde(default)]
+        color: Option<[f32; 4]>,
+    },
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Kind::Panel
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Node {
+    pub id: String,
+    #[serde(default)]
+    pub kind: Kind,
+    #[serde(default = "default_anchor")]
+    pub anchor: amethyst::ui::Anchor,
+    #[serde(default)]
+    pub x: Dim,
+    #[serde(default)]
+    pub y: Dim,
+    #[serde(default)]
+    pub z: f32,
+    #[serde(default)]
+    pub width: Dim,
+    #[serde(default)]
+    pub height: Dim,
+    #[serde(default)]
+    pub stretch: Stretch,
+    ///Order of keyboard focus, nodes with 0 are skipped.
+    #[serde(default)]
+    pub tab_order: i32,
+    ///Background image, for buttons it is `Button` by default.
+    #[serde(default)]
+    pub image: Option<Image>,
+    #[serde(default)]
+    pub text: Option<Text>,
+    ///Whether node receives clicks. Buttons are always interactive.
+    #[serde(default)]
+    pub interactive: bool,
+    ///Whether node blocks clicks on nodes beneath it.
+    #[serde(default = "default_true")]
+    pub opaque: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn placement(&self, root: bool) -> Placement {
+        Placement {
+            anchor: self.anchor,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            stretch: self.stretch,
+            root,
+        }
+    }
+}
+
+///Position of node, which is re-applied on screen resize.
+#[derive(Clone, Copy)]
+struct Placement {
+    anchor: amethyst::ui::Anchor,
+    x: Dim,
+    y: Dim,
+    width: Dim,
+    height: Dim,
+    stretch: Stretch,
+    //Top level node, which needs to be kept within viewport
+    root: bool,
+}
+
+impl Placement {
+    fn apply(&self, transform: &mut amethyst::ui::UiTransform, dimensions: (f32, f32)) {
+        let viewport = Viewport::new(dimensions);
+        let (offset, bars) = if self.root {
+            (viewport.anchor_offset(self.anchor), viewport.bars)
+        } else {
+            ((0.0, 0.0), (0.0, 0.0))
+        };
+
+        transform.local_x = viewport.to_screen(self.x.resolve(camera::WIDTH)) + offset.0;
+        transform.local_y = viewport.to_screen(self.y.resolve(camera::HEIGHT)) + offset.1;
+        transform.width = viewport.to_screen(self.width.resolve(camera::WIDTH));
+        transform.height = viewport.to_screen(self.height.resolve(camera::HEIGHT));
+        transform.stretch = self.stretch.to_screen(&viewport, bars);
+    }
+
+    fn resize(self) -> amethyst::ui::UiResize {
+        amethyst::ui::UiResize::new(move |transform: &mut amethyst::ui::UiTransform, dimensions: (f32, f32)| self.apply(transform, dimensions))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Definition {
+    nodes: Vec<Node>,
+}
+
+impl Definition {
+    ///Parses layout and verifies that ids of nodes are unique.
+    fn parse(path: &Path, data: &str) -> Result<Self, Error> {
+        let definition: Self = ron::de::from_str(data).map_err(|error| Error::Parse(path.to_path_buf(), error))?;
+
+        let mut ids = Vec::new();
+        let mut nodes: Vec<&Node> = definition.nodes.iter().collect();
+        while let Some(node) = nodes.pop() {
+            if ids.contains(&node.id.as_str()) {
+                return Err(Error::DuplicateNode(path.to_path_buf(), node.id.clone()));
+            }
+            ids.push(node.id.as_str());
+            nodes.extend(node.children.iter());
+        }
+
+        Ok(definition)
+    }
+}
+
+///Entities created from layout file.
+pub struct Layout {
+    path: PathBuf,
+    //In order of creation, so parents go before their children
+    entities: Vec<(String, amethyst::ecs::Entity)>,
+}
+
+impl Layout {
+    ///Returns path to layout with specified name.
+    pub fn path(name: &str) -> PathBuf {
+        Path::new(LAYOUT_DIR).join(format!("{}.ron", name))
+    }
+
+    ///Loads `assets/ui/{name}.ron` and creates its entities.
+    pub fn load(world: &mut World, resources: &Resources, name: &str) -> Result<Self, Error> {
+        let path = Self::path(name);
+        let data = fs::read_to_string(&path).map_err(|error| Error::Io(path.clone(), error))?;
+
+        Self::create_from(world, resources, path, &data)
+    }
+
+    ///Creates entities of layout that is built into game.
+    pub fn builtin(world: &mut World, resources: &Resources, name: &str) -> Result<Self, Error> {
+        let path = Self::path(name);
+
+        match BUILTIN.iter().find(|(builtin, _)| *builtin == name) {
+            Some((_, data)) => Self::create_from(world, resources, path, data),
+            None => Err(Error::Io(path, io::ErrorKind::NotFound.into())),
+        }
+    }
+
+    fn create_from(world: &mut World, resources: &Resources, path: PathBuf, data: &str) -> Result<Self, Error> {
+        //Verify before creating anything, so that nothing is left behind on error
+        let definition = Definition::parse(&path, data)?;
+
+        let mut layout = Self {
+            path,
+            entities: Vec::new(),
+        };
+
+        let screen_dimensions = {
+            let screen_dimensions = world.read_resource::<amethyst::renderer::ScreenDimensions>();
+            (screen_dimensions.width(), screen_dimensions.height())
+        };
+
+        for node in definition.nodes.iter() {
+            layout.create(world, resources, node, None, screen_dimensions);
+        }
+
+        Ok(layout)
+    }
+
+    fn create(&mut self, world: &mut World, resources: &Resources, node: &Node, parent: Option<amethyst::ecs::Entity>, screen: (f32, f32)) {
+        let placement = node.placement(parent.is_none());
+        let scale = Viewport::new(screen).scale;
+
+        let entity = match &node.kind {
+            Kind::Panel => {
+                let mut transform = amethyst::ui::UiTransform::new(
+                    node.id.clone(),
+                    node.anchor,
+                    0.0, 0.0, node.z,
+                    0.0, 0.0,
+                    node.tab_order
+                );
+                placement.apply(&mut transform, screen);
+                transform.opaque = node.opaque;
+
+                let mut entity = world.create_entity().with(transform);
+
+                if let Some(image) = node.image {
+                    entity = entity.with(amethyst::ui::UiImage { texture: image.texture(resources) });
+                }
+
+                if let Some(text) = node.text.as_ref() {
+                    let mut ui_text = amethyst::ui::UiText::new(resources.font.clone(), text.text.clone(), text.color.unwrap_or(builder::DEFAULT_TXT_COLOR), text.font_size * scale);
+                    ui_text.align = text.align;
+                    if text.wrap {
+                        ui_text.line_mode = amethyst::ui::LineMode::Wrap;
+                    }
+                    entity = entity.with(ui_text).with(VirtualFont(text.font_size));
+                }
+
+                if node.interactive {
+                    entity = entity.with(amethyst::ui::MouseReactive);
+                }
+
+                if let Some(parent) = parent {
+                    entity = entity.with(amethyst::core::transform::components::Parent { entity: parent });
+                }
+
+                entity.build()
+            },
+            Kind::Button { text, font_size, color } => {
+                let image = node.image.unwrap_or(Image::Button);
+                let mut button = amethyst::ui::UiButtonBuilder::new(node.id.as_str(), text.as_str()).with_font(resources.font.clone())
+                                                                                                    .with_image(image.texture(resources))
+                                                                                                    .with_anchor(node.anchor)
+                                                                                                    .with_layer(node.z)
+                                                                                                    .with_tab_order(node.tab_order)
+                                                                                                    .with_font_size(font_size * scale);
+
+                if image == Image::Button {
+                    button = button.with_hover_image(resources.background.menu_button_hover.clone())
+                                   .with_press_image(resources.background.menu_button_clicked.clone());
+                }
+
+                if let Some(color) = color {
+                    button = button.with_text_color(*color);
+                }
+
+                if let Some(parent) = parent {
+                    button = button.with_parent(parent);
+                }
+
+                let entity = button.build_from_world(world);
+
+                if let Some(transform) = world.write_storage::<amethyst::ui::UiTransform>().get_mut(entity) {
+                    placement.apply(transform, screen);
+                }
+                builder::scale_button_font(world, entity, *font_size);
+
+                entity
+            },
+        };
+
+        world.write_storage::<amethyst::ui::UiResize>().insert(entity, placement.resize()).expect("To add UiResize");
+
+        if node.hidden {
+            world.write_storage::<amethyst::renderer::Hidden>().insert(entity, amethyst::renderer::Hidden).expect("To add Hidden");
+        }
+
+        self.entities.push((node.id.clone(), entity));
+
+        for child in node.children.iter() {
+            self.create(world, resources, child, Some(entity), screen);
+        }
+    }
+
+    ///Returns entity of node with specified `id`.
+    pub fn get(&self, id: &str) -> Option<amethyst::ecs::Entity> {
+        self.entities.iter().find(|(name, _)| name == id).map(|(_, entity)| *entity)
+    }
+
+    ///Returns entity of node that is expected to be in layout.
+    pub fn entity(&self, id: &str) -> Result<amethyst::ecs::Entity, Error> {
+        self.get(id).ok_or_else(|| Error::MissingNode(self.path.clone(), id.to_owned()))
+    }
+
+    ///Looks up entities that screen needs with `lookup`.
+    ///
+    ///Layout is destroyed if some of them is missing.
+    pub fn bind<T, F: FnOnce(&Self) -> Result<T, Error>>(self, world: &mut World, lookup: F) -> Result<(Self, T), Error> {
+        match lookup(&self) {
+            Ok(result) => Ok((self, result)),
+            Err(error) => {
+                self.destroy(world);
+                Err(error)
+            }
+        }
+    }
+
+    ///Returns `id` of node that corresponds to entity.
+    pub fn find(&self, entity: amethyst::ecs::Entity) -> Option<&str> {
+        self.entities.iter().find(|(_, node)| *node == entity).map(|(name, _)| name.as_str())
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        for (_, entity) in self.entities {
+            let _ = world.delete_entity(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &str) -> Result<Definition, Error> {
+        Definition::parse(Path::new("test.ron"), data)
+    }
+
+    //Returns tab orders of all nodes
+    fn tab_orders(nodes: &[Node], result: &mut Vec<i32>) {
+        for node in nodes {
+            result.push(node.tab_order);
+            tab_orders(&node.children, result);
+        }
+    }
+
+    #[test]
+    fn parse_builtin_layouts() {
+        for (name, data) in BUILTIN.iter() {
+            let definition = Definition::parse(&Layout::path(name), data).unwrap_or_else(|error| panic!("{}", error));
+
+            //Focus order of interactive nodes must be unambiguous
+            let mut orders = Vec::new();
+            tab_orders(&definition.nodes, &mut orders);
+            orders.retain(|order| *order != 0);
+            let len = orders.len();
+            orders.sort();
+            orders.dedup();
+            assert_eq!(orders.len(), len, "{}: tab orders are not unique", name);
+        }
+    }
+
+    #[test]
+    fn parse_layout_files() {
+        let mut count = 0;
+        for entry in fs::read_dir(LAYOUT_DIR).expect("To read layout directory") {
+            let path = entry.expect("To read layout directory").path();
+            if path.extension().map(|extension| extension != "ron").unwrap_or(true) {
+                continue;
+            }
+
+            let data = fs::read_to_string(&path).expect("To read layout");
+            if let Err(error) = Definition::parse(&path, &data) {
+                panic!("{}", error);
+            }
+            count += 1;
+        }
+
+        assert_eq!(count, BUILTIN.len());
+    }
+
+    #[test]
+    fn resolve_dims() {
+        assert_eq!(Dim::Px(35.0).resolve(camera::WIDTH), 35.0);
+        assert_eq!(Dim::Percent(25.0).resolve(camera::WIDTH), 480.0);
+        assert_eq!(Dim::Percent(50.0).resolve(camera::HEIGHT), 540.0);
+        assert_eq!(Dim::default().resolve(camera::HEIGHT), 0.0);
+
+        let definition = parse("(nodes: [(id: \"panel\", x: Percent(10.0), width: Px(300.0))])").expect("To parse layout");
+        let node = &definition.nodes[0];
+        assert_eq!(node.x.resolve(camera::WIDTH), 192.0);
+        assert_eq!(node.y.resolve(camera::HEIGHT), 0.0);
+        assert_eq!(node.width.resolve(camera::WIDTH), 300.0);
+    }
+
+    #[test]
+    fn parse_tab_order() {
+        let definition = parse("(nodes: [(id: \"first\", tab_order: 2, children: [(id: \"second\")])])").expect("To parse layout");
+        assert_eq!(definition.nodes[0].tab_order, 2);
+        assert_eq!(definition.nodes[0].children[0].tab_order, 0);
+    }
+
+    #[test]
+    fn reject_duplicate_nodes() {
+        match parse("(nodes: [(id: \"title\", children: [(id: \"title\")])])") {
+            Err(Error::DuplicateNode(path, id)) => {
+                assert_eq!(path, Path::new("test.ron"));
+                assert_eq!(id, "title");
+            },
+            Err(error) => panic!("Unexpected error: {}", error),
+            Ok(_) => panic!("Duplicate node is accepted"),
+        }
+    }
+
+    #[test]
+    fn report_missing_nodes() {
+        let mut world = World::new();
+        let title = world.create_entity().build();
+        let layout = Layout {
+            path: PathBuf::from("test.ron"),
+            entities: vec![("title".to_owned(), title)],
+        };
+
+        assert_eq!(layout.entity("title").ok(), Some(title));
+        match layout.entity("back") {
+            Err(Error::MissingNode(_, id)) => assert_eq!(id, "back"),
+            _ => panic!("Missing node is found"),
+        }
+
+        //Layout is destroyed when screen cannot find its nodes
+        match layout.bind(&mut world, |layout| layout.entity("back")) {
+            Err(Error::MissingNode(path, id)) => {
+                assert_eq!(path, Path::new("test.ron"));
+                assert_eq!(id, "back");
+            },
+            _ => panic!("Missing node is found"),
+        }
+        assert!(!world.is_alive(title));
+    }
+}
@@ -1,90 +1,229 @@
-use amethyst::prelude::{Builder, World};
+use amethyst::prelude::World;
 use amethyst::ecs as specs;
 
+use super::typewriter::Typewriter;
+use super::camera::Viewport;
+
 mod res;
 mod builder;
+pub mod layout;
+pub mod slots;
+pub mod backlog;
+pub mod settings;
+pub mod pause;
 
 pub use self::res::Resources;
+pub use self::layout::Layout;
+pub use self::slots::Slots;
+pub use self::backlog::Backlog;
+pub use self::settings::Settings;
+pub use self::pause::Pause;
 
 ///Describes UI component interfaces
-pub trait UiComponent {
-    fn new(world: &mut World, resources: &Resources) -> Self;
+pub trait UiComponent: Sized {
+    ///Creates component from its layout file.
+    fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error>;
+    ///Creates component from builtin layout, in case layout file cannot be used.
+    fn builtin(world: &mut World, resources: &Resources) -> Self;
     fn destroy(self, world: &mut World);
 }
 
+///Title screen, described by `assets/ui/menu.ron`.
 pub struct Menu {
-    background: amethyst::ecs::Entity,
-    pub new_game_btn: amethyst::ecs::Entity,
-    pub exit_game_btn: amethyst::ecs::Entity,
+    layout: Layout,
+}
+
+impl Menu {
+    ///Returns layout `id` of clicked element.
+    pub fn find(&self, entity: amethyst::ecs::Entity) -> Option<&str> {
+        self.layout.find(entity)
+    }
 }
 
 impl UiComponent for Menu {
-    fn new(world: &mut World, resources: &Resources) -> Self {
-        let background = amethyst::ui::UiImage { texture: resources.background.menu.clone() };
-        let mut overlay_transform = amethyst::ui::UiTransform::new(
-            "Background".to_string(),
-            amethyst::ui::Anchor::Middle,
-            0.0, 0.0, 1.5,
-            1.0, 1.0,
-            0
-        );
-        overlay_transform.stretch = amethyst::ui::Stretch::XY {
-            x_margin: 0.0,
-            y_margin: 0.0,
-        };
+    fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error> {
+        Ok(Self {
+            layout: Layout::load(world, resources, "menu")?,
+        })
+    }
 
-        let background = world.create_entity()
-                              .with(overlay_transform)
-                              .with(background)
-                              .build();
+    fn builtin(world: &mut World, resources: &Resources) -> Self {
+        Self {
+            layout: Layout::builtin(world, resources, "menu").expect("To create builtin title screen"),
+        }
+    }
 
-        let screen_dimensions = {
-            let screen_dimensions = world.read_resource::<amethyst::renderer::ScreenDimensions>();
-            (screen_dimensions.width(), screen_dimensions.height())
-        };
+    fn destroy(self, world: &mut World) {
+        self.layout.destroy(world);
+    }
+}
 
-        let screen_dimensions = builder::get_button_size(screen_dimensions);
+//In virtual pixels
+const CHOICE_SPACING: f32 = 140.0;
+//Shift choices up, so that they do not overlap with text window
+const CHOICE_OFFSET: f32 = 140.0;
 
-        let new_game_btn = builder::menu_button("btn_new_game", "Start", resources, screen_dimensions).with_position(0.0, -100.0)
-                                                                                                      .build_from_world(world);
-        let exit_game_btn = builder::menu_button("btn_exit_game", "Exit", resources, screen_dimensions).with_position(0.0, -225.0)
-                                                                                                       .build_from_world(world);
+///Overlay with choice options.
+pub struct Choices {
+    //Button per option, in order of options
+    buttons: Vec<amethyst::ecs::Entity>,
+}
+
+impl Choices {
+    pub fn new(world: &mut World, resources: &Resources, choices: &[crate::game::script::Choice]) -> Self {
+        let viewport = Viewport::from_screen(&world.read_resource::<amethyst::renderer::ScreenDimensions>());
+        let size = builder::get_choice_size(&viewport);
+        let font_size = viewport.to_screen(builder::CHOICE_FONT_SIZE);
+
+        let top = (choices.len() - 1) as f32 * CHOICE_SPACING / 2.0 + CHOICE_OFFSET;
+        let mut buttons = Vec::with_capacity(choices.len());
 
-        world.write_storage::<amethyst::ui::UiResize>().insert(new_game_btn, amethyst::ui::UiResize::new(builder::resize_button)).expect("To add UiResize");
-        world.write_storage::<amethyst::ui::UiResize>().insert(exit_game_btn, amethyst::ui::UiResize::new(builder::resize_button)).expect("To add UiResize");
+        for (idx, choice) in choices.iter().enumerate() {
+            let name = format!("btn_choice_{}", idx);
+            let y = top - idx as f32 * CHOICE_SPACING;
 
+            let button = if choice.enabled {
+                builder::menu_button(&name, &choice.text, resources, size).with_position(0.0, viewport.to_screen(y))
+                                                                          .with_font_size(font_size)
+                                                                          .build_from_world(world)
+            } else {
+                let button = builder::disabled_button(&name, &choice.text, resources, size).with_position(0.0, viewport.to_screen(y))
+                                                                                          .with_font_size(font_size)
+                                                                                          .build_from_world(world);
+                world.write_storage::<amethyst::ui::MouseReactive>().remove(button);
+                button
+            };
+
+            world.write_storage::<amethyst::ui::UiResize>().insert(button, builder::resize_choice(y)).expect("To add UiResize");
+            builder::scale_button_font(world, button, builder::CHOICE_FONT_SIZE);
+            buttons.push(button);
+        }
 
         Self {
-            background,
-            new_game_btn,
-            exit_game_btn,
+            buttons
         }
     }
 
-    fn destroy(self, world: &mut World) {
-        let _ = world.delete_entity(self.background);
-        let _ = world.delete_entity(self.new_game_btn);
-        let _ = world.delete_entity(self.exit_game_btn);
+    ///Returns index of option that corresponds to button.
+    pub fn find(&self, button: amethyst::ecs::Entity) -> Option<usize> {
+        self.buttons.iter().position(|entity| *entity == button)
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        for button in self.buttons {
+            let _ = world.delete_entity(button);
+        }
     }
 }
 
+///Colour of speaker's name when character doesn't specify it.
+pub const DEFAULT_NAME_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
 pub struct TextWindow {
     pub window: amethyst::ecs::Entity,
     pub text: amethyst::ecs::Entity,
+    ///Name plate with speaker's name.
+    pub name: amethyst::ecs::Entity,
     pub close: amethyst::ecs::Entity,
+    ///Toggles auto mode.
+    pub auto: amethyst::ecs::Entity,
 }
 
 impl TextWindow {
-    pub fn get_size(dimensions: (f32, f32)) -> (f32, f32) {
-        //For width we rely on resize as of now
-        (0.0, dimensions.1 / 3.84)
+    fn from_layout(layout: &Layout) -> Result<Self, layout::Error> {
+        Ok(Self {
+            window: layout.entity("window")?,
+            text: layout.entity("text")?,
+            name: layout.entity("name")?,
+            close: layout.entity("close")?,
+            auto: layout.entity("auto")?,
+        })
+    }
+
+    ///Replaces text within window.
+    pub fn set_text<S: Into<String>>(&self, world: &mut World, text: S) {
+        match world.write_storage::<amethyst::ui::UiText>().get_mut(self.text) {
+            Some(ui_text) => ui_text.text = text.into(),
+            None => unreach!(),
+        }
     }
 
-    fn resize(transform: &mut amethyst::ui::UiTransform, dimensions: (f32, f32)) {
-        let (_, height) = Self::get_size(dimensions);
-        transform.height = height;
-        //Approx number from empirical observation
-        transform.local_y = dimensions.1 / 6.981;
+    ///Shows name of speaker above text, or hides name plate for narration.
+    ///
+    ///Name plate is hidden with its own `HiddenPropagate`,
+    ///as `Hidden` of children is removed once hidden window is shown again.
+    pub fn set_speaker(&self, world: &mut World, speaker: Option<&str>, color: [f32; 4]) {
+        match speaker {
+            Some(speaker) => {
+                match world.write_storage::<amethyst::ui::UiText>().get_mut(self.name) {
+                    Some(ui_text) => {
+                        ui_text.text = speaker.to_owned();
+                        ui_text.color = color;
+                    },
+                    None => unreach!(),
+                }
+                world.write_storage::<amethyst::renderer::HiddenPropagate>().remove(self.name);
+                //Hidden window keeps its children hidden
+                if !self.is_hidden(world) {
+                    world.write_storage::<amethyst::renderer::Hidden>().remove(self.name);
+                }
+            },
+            None => {
+                world.write_storage::<amethyst::renderer::HiddenPropagate>().insert(self.name, amethyst::renderer::HiddenPropagate::default()).expect("To hide name plate");
+            },
+        }
+    }
+
+    ///Returns whether text is still being revealed.
+    pub fn is_typing(&self, world: &World) -> bool {
+        match world.read_storage::<Typewriter>().get(self.text) {
+            Some(typewriter) => !typewriter.is_finished(),
+            None => false,
+        }
+    }
+
+    ///Highlights auto button when auto mode is enabled.
+    pub fn set_auto(&self, world: &mut World, resources: &Resources, enabled: bool) {
+        let texture = if enabled {
+            resources.background.menu_button_hover.clone()
+        } else {
+            resources.adv.close_background.clone()
+        };
+
+        match world.write_storage::<amethyst::ui::UiImage>().get_mut(self.auto) {
+            Some(image) => image.texture = texture,
+            None => unreach!(),
+        }
+    }
+
+    ///Returns whether window is hidden.
+    pub fn is_hidden(&self, world: &World) -> bool {
+        world.read_storage::<amethyst::renderer::HiddenPropagate>().contains(self.window)
+    }
+
+    ///Starts revealing text within window character by character.
+    pub fn type_text<S: Into<String>>(&self, world: &mut World, text: S, speed: Option<f32>) {
+        let text = text.into();
+        world.write_storage::<Typewriter>().insert(self.text, Typewriter::new(&text, speed)).expect("To add Typewriter");
+        self.set_text(world, "");
+    }
+
+    ///Reveals whole text at once.
+    ///
+    ///Returns `false` if text is already fully revealed.
+    pub fn complete_text(&self, world: &mut World) -> bool {
+        let text = match world.write_storage::<Typewriter>().get_mut(self.text) {
+            Some(typewriter) => if typewriter.is_finished() {
+                return false;
+            } else {
+                typewriter.complete();
+                typewriter.visible()
+            },
+            None => return false,
+        };
+
+        self.set_text(world, text);
+        true
     }
 
     ///Toggles hidden property of window.
@@ -92,44 +231,50 @@ impl TextWindow {
         match world.write_storage::<amethyst::renderer::HiddenPropagate>().entry(self.window).expect("To get hidden component") {
             specs::storage::StorageEntry::Occupied(occupied) => {
                 occupied.remove();
-                world.write_storage::<amethyst::ui::MouseReactive>()
-                     .insert(self.close, amethyst::ui::MouseReactive)
-                     .expect("Add MouseReactive to close button");
+                let mut reactive = world.write_storage::<amethyst::ui::MouseReactive>();
+                reactive.insert(self.close, amethyst::ui::MouseReactive).expect("Add MouseReactive to close button");
+                reactive.insert(self.auto, amethyst::ui::MouseReactive).expect("Add MouseReactive to auto button");
+                reactive.insert(self.window, amethyst::ui::MouseReactive).expect("Add MouseReactive to window");
             },
             specs::storage::StorageEntry::Vacant(vacant) => {
                 vacant.insert(amethyst::renderer::HiddenPropagate::default());
-                world.write_storage::<amethyst::ui::MouseReactive>().remove(self.close);
+                let mut reactive = world.write_storage::<amethyst::ui::MouseReactive>();
+                reactive.remove(self.close);
+                reactive.remove(self.auto);
+                reactive.remove(self.window);
             },
         }
     }
 }
 
+///Dialogue screen, described by `assets/ui/adv.ron`.
 pub struct Adv {
+    layout: Layout,
     pub text: TextWindow,
 }
 
 impl UiComponent for Adv {
-    fn new(world: &mut World, resources: &Resources) -> Self {
-        let text = builder::TextWindow::default().name("adv_text".to_owned())
-                                                 .text("Text example")
-                                                 .font(resources.font.clone(), 40.0)
-                                                 .position(0.0.into(), 110.0.into(), None)
-                                                 .width(0.0)
-                                                 .height(200.0)
-                                                 .anchor(amethyst::ui::Anchor::BottomMiddle)
-                                                 .background(resources.adv.text_background.clone())
-                                                 .stretch(amethyst::ui::Stretch::X { x_margin: 10.0, })
-                                                 .resize(Box::new(TextWindow::resize))
-                                                 .close_background(resources.adv.close_background.clone())
-                                                 .build(world);
+    fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error> {
+        let layout = Layout::load(world, resources, "adv")?;
+        let (layout, text) = layout.bind(world, TextWindow::from_layout)?;
+
+        Ok(Self {
+            layout,
+            text
+        })
+    }
+
+    fn builtin(world: &mut World, resources: &Resources) -> Self {
+        let layout = Layout::builtin(world, resources, "adv").expect("To create builtin dialogue screen");
+        let (layout, text) = layout.bind(world, TextWindow::from_layout).expect("To create builtin dialogue screen");
 
         Self {
+            layout,
             text
         }
     }
 
     fn destroy(self, world: &mut World) {
-        let _ = world.delete_entity(self.text.window);
-        let _ = world.delete_entity(self.text.text);
+        self.layout.destroy(world);
     }
 }
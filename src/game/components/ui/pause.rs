@@ -0,0 +1,34 @@
+use amethyst::prelude::World;
+
+use super::{layout, Layout, Resources};
+
+///In-game menu, shown over the game.
+///
+///Described by `assets/ui/pause.ron`.
+pub struct Pause {
+    layout: Layout,
+}
+
+impl Pause {
+    pub fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error> {
+        Ok(Self {
+            layout: Layout::load(world, resources, "pause")?,
+        })
+    }
+
+    ///Creates screen from builtin layout, in case layout file cannot be used.
+    pub fn builtin(world: &mut World, resources: &Resources) -> Self {
+        Self {
+            layout: Layout::builtin(world, resources, "pause").expect("To create builtin pause screen"),
+        }
+    }
+
+    ///Returns layout `id` of clicked element.
+    pub fn find(&self, entity: amethyst::ecs::Entity) -> Option<&str> {
+        self.layout.find(entity)
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        self.layout.destroy(world);
+    }
+}
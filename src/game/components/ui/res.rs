@@ -4,6 +4,8 @@ use amethyst::assets::SimpleFormat;
 pub const DARK_BUTTON: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
 pub const DARK_BUTTON_HOVER: [f32; 4] = [128.0, 0.0, 128.0, 0.75]; //dark purple
 pub const DARK_BUTTON_CLICK: [f32; 4] = [138.0, 0.0, 138.0, 0.95]; //dark purple
+pub const DARK_BUTTON_DISABLED: [f32; 4] = [0.0, 0.0, 0.0, 0.25];
+pub const OVERLAY: [f32; 4] = [0.0, 0.0, 0.0, 0.85];
 pub const MENU_IMG: &'static [u8] = include_bytes!("../../../../assets/background/menu.png");
 
 pub const BLACK_BUTTON: [f32; 4] = [128.0, 128.0, 128.0, 1.0];
@@ -33,7 +35,11 @@ pub struct Background {
     pub menu_button: amethyst::renderer::TextureHandle,
     pub menu_button_hover: amethyst::renderer::TextureHandle,
     pub menu_button_clicked: amethyst::renderer::TextureHandle,
-    pub menu: amethyst::renderer::TextureHandle
+    pub menu_button_disabled: amethyst::renderer::TextureHandle,
+    pub overlay: amethyst::renderer::TextureHandle,
+    pub menu: amethyst::renderer::TextureHandle,
+    ///Letterbox bars.
+    pub black: amethyst::renderer::TextureHandle,
 }
 
 impl Background {
@@ -41,14 +47,20 @@ impl Background {
         let menu_button = world.read_resource::<amethyst::assets::Loader>().load_from_data(DARK_BUTTON.into(), (), &world.read_resource());
         let menu_button_hover = world.read_resource::<amethyst::assets::Loader>().load_from_data(DARK_BUTTON_HOVER.into(), (), &world.read_resource());
         let menu_button_clicked = world.read_resource::<amethyst::assets::Loader>().load_from_data(DARK_BUTTON_CLICK.into(), (), &world.read_resource());
+        let menu_button_disabled = world.read_resource::<amethyst::assets::Loader>().load_from_data(DARK_BUTTON_DISABLED.into(), (), &world.read_resource());
+        let overlay = world.read_resource::<amethyst::assets::Loader>().load_from_data(OVERLAY.into(), (), &world.read_resource());
         let menu = amethyst::renderer::PngFormat.import(MENU_IMG.to_owned(), amethyst::renderer::TextureMetadata::srgb()).expect("To import builtin image");
         let menu = world.read_resource::<amethyst::assets::Loader>().load_from_data(menu, (), &world.read_resource());
+        let black = world.read_resource::<amethyst::assets::Loader>().load_from_data([0.0, 0.0, 0.0, 1.0].into(), (), &world.read_resource());
 
         Self {
             menu_button,
             menu_button_hover,
             menu_button_clicked,
+            menu_button_disabled,
+            overlay,
             menu,
+            black,
         }
     }
 }
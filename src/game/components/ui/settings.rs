@@ -0,0 +1,167 @@
+use amethyst::prelude::World;
+
+use super::{layout, Layout, Resources};
+use crate::game::settings::{self, Control, Item};
+
+///Control of setting on screen.
+enum Widget {
+    Choice {
+        prev: amethyst::ecs::Entity,
+        next: amethyst::ecs::Entity,
+    },
+    Toggle(amethyst::ecs::Entity),
+    Slider {
+        track: amethyst::ecs::Entity,
+        fill: amethyst::ecs::Entity,
+    },
+}
+
+struct Row {
+    item: Item,
+    value: amethyst::ecs::Entity,
+    widget: Widget,
+}
+
+///Change of setting that is requested by click.
+pub enum Action {
+    ///Moves to the next or previous value, toggles are flipped in either direction.
+    Change(Item, bool),
+    ///Starts moving slider with mouse.
+    Slide(Item),
+}
+
+///Settings screen with row per setting.
+///
+///Setting is changed by `<` and `>` buttons, toggle button or slider, depending on its `Control`.
+///Described by `assets/ui/settings.ron`, which has `label{N}` and `value{N}` nodes per setting,
+///along with `prev{N}` and `next{N}`, `toggle{N}`, or `slider{N}` and `fill{N}` nodes for its control.
+pub struct Settings {
+    layout: Layout,
+    rows: Vec<Row>,
+    pub back: amethyst::ecs::Entity,
+}
+
+impl Settings {
+    pub fn new(world: &mut World, resources: &Resources) -> Result<Self, layout::Error> {
+        let layout = Layout::load(world, resources, "settings")?;
+        Self::from_layout(world, layout)
+    }
+
+    ///Creates screen from builtin layout, in case layout file cannot be used.
+    pub fn builtin(world: &mut World, resources: &Resources) -> Self {
+        Layout::builtin(world, resources, "settings").and_then(|layout| Self::from_layout(world, layout))
+                                                     .expect("To create builtin settings screen")
+    }
+
+    fn from_layout(world: &mut World, layout: Layout) -> Result<Self, layout::Error> {
+        let (layout, (labels, rows, back)) = layout.bind(world, |layout| {
+            let mut labels = Vec::with_capacity(Item::ALL.len());
+            let mut rows = Vec::with_capacity(Item::ALL.len());
+            for (idx, item) in Item::ALL.iter().enumerate() {
+                let widget = match item.control() {
+                    Control::Choice => Widget::Choice {
+                        prev: layout.entity(&format!("prev{}", idx))?,
+                        next: layout.entity(&format!("next{}", idx))?,
+                    },
+                    Control::Toggle => Widget::Toggle(layout.entity(&format!("toggle{}", idx))?),
+                    Control::Slider => Widget::Slider {
+                        track: layout.entity(&format!("slider{}", idx))?,
+                        fill: layout.entity(&format!("fill{}", idx))?,
+                    },
+                };
+
+                labels.push(layout.entity(&format!("label{}", idx))?);
+                rows.push(Row {
+                    item: *item,
+                    value: layout.entity(&format!("value{}", idx))?,
+                    widget,
+                });
+            }
+            Ok((labels, rows, layout.entity("back")?))
+        })?;
+
+        {
+            let mut texts = world.write_storage::<amethyst::ui::UiText>();
+            for (label, row) in labels.iter().zip(rows.iter()) {
+                if let Some(text) = texts.get_mut(*label) {
+                    text.text = row.item.name().to_owned();
+                }
+            }
+        }
+
+        //Fill is sized relative to its track, so it keeps its level on resize
+        let mut transforms = world.write_storage::<amethyst::ui::UiTransform>();
+        let mut resizes = world.write_storage::<amethyst::ui::UiResize>();
+        for row in rows.iter() {
+            if let Widget::Slider { fill, .. } = row.widget {
+                resizes.remove(fill);
+                if let Some(transform) = transforms.get_mut(fill) {
+                    transform.scale_mode = amethyst::ui::ScaleMode::Percent;
+                    transform.local_y = 0.0;
+                    transform.height = 1.0;
+                }
+            }
+        }
+
+        Ok(Self {
+            layout,
+            rows,
+            back,
+        })
+    }
+
+    ///Updates values of settings and levels of sliders.
+    pub fn refresh(&self, world: &mut World, settings: &settings::Settings) {
+        let mut texts = world.write_storage::<amethyst::ui::UiText>();
+        let mut transforms = world.write_storage::<amethyst::ui::UiTransform>();
+
+        for row in self.rows.iter() {
+            if let Some(text) = texts.get_mut(row.value) {
+                text.text = settings.value(row.item);
+            }
+
+            if let (Widget::Slider { fill, .. }, Some(level)) = (&row.widget, settings.level(row.item)) {
+                if let Some(transform) = transforms.get_mut(*fill) {
+                    //Anchored to the left edge of track, so it is centered at half of its width
+                    transform.width = level;
+                    transform.local_x = level / 2.0;
+                }
+            }
+        }
+    }
+
+    ///Returns change of setting that corresponds to clicked node.
+    pub fn find(&self, target: amethyst::ecs::Entity) -> Option<Action> {
+        self.rows.iter().find_map(|row| match row.widget {
+            Widget::Choice { prev, .. } if prev == target => Some(Action::Change(row.item, false)),
+            Widget::Choice { next, .. } if next == target => Some(Action::Change(row.item, true)),
+            Widget::Toggle(toggle) if toggle == target => Some(Action::Change(row.item, true)),
+            Widget::Slider { track, .. } if track == target => Some(Action::Slide(row.item)),
+            _ => None,
+        })
+    }
+
+    ///Returns level of slider under horizontal position of cursor on screen.
+    ///
+    ///Cursor outside of track gives the nearest end.
+    pub fn slider_level(&self, world: &World, item: Item, cursor_x: f32) -> Option<f32> {
+        let track = self.rows.iter().find_map(|row| match row.widget {
+            Widget::Slider { track, .. } if row.item == item => Some(track),
+            _ => None,
+        })?;
+
+        let transforms = world.read_storage::<amethyst::ui::UiTransform>();
+        let transform = transforms.get(track)?;
+        let width = transform.pixel_width();
+        if width <= 0.0 {
+            return None;
+        }
+
+        let left = transform.pixel_x() - width / 2.0;
+        Some(((cursor_x - left) / width).max(0.0).min(1.0))
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        self.layout.destroy(world);
+    }
+}
@@ -0,0 +1,122 @@
+use amethyst::prelude::World;
+
+use super::{layout, Layout, Resources};
+use crate::game::save;
+
+//Number of characters of line shown in slot
+const PREVIEW_LEN: usize = 60;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Save,
+    Load,
+}
+
+impl Mode {
+    fn title(self) -> &'static str {
+        match self {
+            Mode::Save => "Save",
+            Mode::Load => "Load",
+        }
+    }
+}
+
+///Returns description of save slot.
+fn describe(slot: u32) -> String {
+    match save::Save::read(slot) {
+        Ok(save) => {
+            let mut preview = save.preview.chars().take(PREVIEW_LEN).collect::<String>();
+            if preview.len() < save.preview.len() {
+                preview.push('…');
+            }
+
+            format!("Slot {} - {}\n{}\n{}", slot, save::format_timestamp(save.timestamp), save.chapter.as_ref().map(String::as_str).unwrap_or(""), preview)
+        },
+        Err(save::Error::Io(_, ref error)) if error.kind() == std::io::ErrorKind::NotFound => format!("Slot {}\nEmpty", slot),
+        Err(error) => {
+            warn!("Slot {}: {}", slot, error);
+            format!("Slot {}\nUnable to read save", slot)
+        }
+    }
+}
+
+///Save/Load screen with grid of slots.
+///
+///Described by `assets/ui/slots.ron`, which has `slot{N}` node with `slot_text{N}` child per slot on page.
+pub struct Slots {
+    layout: Layout,
+    title: amethyst::ecs::Entity,
+    //Slot buttons and their text
+    slots: Vec<(amethyst::ecs::Entity, amethyst::ecs::Entity)>,
+    pub prev: amethyst::ecs::Entity,
+    pub next: amethyst::ecs::Entity,
+    pub back: amethyst::ecs::Entity,
+}
+
+impl Slots {
+    pub fn new(world: &mut World, resources: &Resources, mode: Mode, page: u32) -> Result<Self, layout::Error> {
+        let layout = Layout::load(world, resources, "slots")?;
+        let result = Self::from_layout(world, layout)?;
+
+        result.refresh(world, mode, page);
+        Ok(result)
+    }
+
+    ///Creates screen from builtin layout, in case layout file cannot be used.
+    pub fn builtin(world: &mut World, resources: &Resources, mode: Mode, page: u32) -> Self {
+        let result = Layout::builtin(world, resources, "slots").and_then(|layout| Self::from_layout(world, layout))
+                                                               .expect("To create builtin slots screen");
+
+        result.refresh(world, mode, page);
+        result
+    }
+
+    fn from_layout(world: &mut World, layout: Layout) -> Result<Self, layout::Error> {
+        let (layout, (title, slots, (prev, next, back))) = layout.bind(world, |layout| {
+            let mut slots = Vec::with_capacity(save::SLOTS_PER_PAGE as usize);
+            for idx in 0..save::SLOTS_PER_PAGE {
+                slots.push((layout.entity(&format!("slot{}", idx))?, layout.entity(&format!("slot_text{}", idx))?));
+            }
+            Ok((layout.entity("title")?, slots, (layout.entity("prev")?, layout.entity("next")?, layout.entity("back")?)))
+        })?;
+
+        Ok(Self {
+            layout,
+            title,
+            slots,
+            prev,
+            next,
+            back,
+        })
+    }
+
+    ///Updates screen with content of page.
+    pub fn refresh(&self, world: &mut World, mode: Mode, page: u32) {
+        let mut texts = world.write_storage::<amethyst::ui::UiText>();
+
+        if let Some(title) = texts.get_mut(self.title) {
+            title.text = format!("{} - Page {}/{}", mode.title(), page + 1, save::PAGES);
+        }
+
+        for (idx, (_, text)) in self.slots.iter().enumerate() {
+            if let Some(text) = texts.get_mut(*text) {
+                text.text = describe(Self::slot(page, idx));
+            }
+        }
+    }
+
+    ///Returns number of slot by its index on page.
+    pub fn slot(page: u32, idx: usize) -> u32 {
+        //Slot 0 is reserved for quick save
+        page * save::SLOTS_PER_PAGE + idx as u32 + 1
+    }
+
+    ///Returns index of slot on page that corresponds to button.
+    pub fn find(&self, button: amethyst::ecs::Entity) -> Option<usize> {
+        self.slots.iter().position(|(entity, _)| *entity == button)
+    }
+
+    pub fn destroy(self, world: &mut World) {
+        self.layout.destroy(world);
+    }
+}
@@ -1,12 +1,14 @@
-pub fn get_display() -> amethyst::renderer::DisplayConfig {
+use super::settings::{Settings, WindowMode};
+
+pub fn get_display(settings: &Settings) -> amethyst::renderer::DisplayConfig {
     amethyst::renderer::DisplayConfig {
         title: "VN".to_owned(),
-        dimensions: Some((1024, 768)),
+        dimensions: Some(settings.resolution),
         max_dimensions: None,
         min_dimensions: None,
-        fullscreen: false,
+        fullscreen: settings.window_mode == WindowMode::Exclusive,
         multisampling: 1,
         visibility: true,
-        vsync: true,
+        vsync: settings.vsync,
     }
 }
@@ -3,12 +3,35 @@
 use amethyst::prelude::{World};
 use amethyst::core::nalgebra::Matrix;
 
-use std::path;
+use amethyst::assets::SimpleFormat;
+
+use std::{fmt, fs, io, path};
+
+///Longest side of mask, larger masks are downscaled.
+const MASK_SIZE_LIMIT: u32 = 256;
+
+///Format of image, detected by extension.
+pub enum ImageFormat {
+    Png,
+    Jpg,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with(".png") {
+            Some(ImageFormat::Png)
+        } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+            Some(ImageFormat::Jpg)
+        } else {
+            None
+        }
+    }
+}
 
 pub trait TextureLoader {
     fn load_file<F: amethyst::assets::Format<amethyst::renderer::Texture>>(path: &str, format: F, world: &World) -> amethyst::renderer::TextureHandle {
         let loader = world.read_resource::<amethyst::assets::Loader>();
-        loader.load(path, amethyst::renderer::PngFormat, amethyst::renderer::TextureMetadata::srgb(), (), &world.read_resource())
+        loader.load(path, format, amethyst::renderer::TextureMetadata::srgb(), (), &world.read_resource())
     }
 
     fn load_sprite_sheet(path: &str, texture: amethyst::renderer::TextureHandle, world: &World) -> amethyst::renderer::SpriteSheetHandle {
@@ -23,28 +46,117 @@ pub trait TextureLoader {
         loader.load(path, amethyst::renderer::SpriteSheetFormat, texture, (), &world.read_resource())
     }
 
-    fn load(self, world: &World) -> amethyst::renderer::SpriteSheetHandle;
+    fn load(self, world: &World) -> Result<amethyst::renderer::SpriteSheetHandle, SpriteError>;
 }
 
+#[derive(Debug)]
+pub enum SpriteError {
+    UnknownFormat(String),
+}
+
+impl fmt::Display for SpriteError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpriteError::UnknownFormat(path) => write!(fmt, "{}: unknown image format, expected png or jpg", path),
+        }
+    }
+}
+
+impl std::error::Error for SpriteError {}
+
 pub enum Sprite {
-    Path(&'static str)
+    Path(&'static str),
+    File(String),
+}
+
+impl Sprite {
+    fn path(&self) -> &str {
+        match self {
+            Sprite::Path(path) => path,
+            Sprite::File(path) => path.as_str(),
+        }
+    }
 }
 
 impl TextureLoader for Sprite {
-    fn load(self, world: &World) -> amethyst::renderer::SpriteSheetHandle {
+    fn load(self, world: &World) -> Result<amethyst::renderer::SpriteSheetHandle, SpriteError> {
+        let path = self.path();
+        let texture = match ImageFormat::from_path(path) {
+            Some(ImageFormat::Png) => Self::load_file(path, amethyst::renderer::PngFormat, world),
+            Some(ImageFormat::Jpg) => Self::load_file(path, amethyst::renderer::JpgFormat, world),
+            None => return Err(SpriteError::UnknownFormat(path.to_owned())),
+        };
+
+        Ok(Self::load_sprite_sheet(path, texture, world))
+    }
+}
+
+#[derive(Debug)]
+pub enum MaskError {
+    Io(io::Error),
+    Format(String),
+    UnknownFormat,
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Sprite::Path(path) => {
-                let texture = if path.ends_with(".png") {
-                    Self::load_file(path, amethyst::renderer::PngFormat, world)
-                } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
-                    Self::load_file(path, amethyst::renderer::JpgFormat, world)
-                } else {
-                    panic!("Unknown sprite format")
-                };
-
-                Self::load_sprite_sheet(path, texture, world)
+            MaskError::Io(error) => write!(fmt, "{}", error),
+            MaskError::Format(error) => write!(fmt, "invalid image: {}", error),
+            MaskError::UnknownFormat => write!(fmt, "unknown image format"),
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+///Grayscale image, which is used by rule transitions.
+///
+///Unlike sprites it is read on CPU, as texture is generated from its luminance.
+pub struct Mask {
+    pub width: u32,
+    pub height: u32,
+    ///Luminance of pixels from top left corner, row by row.
+    pub luminance: Vec<u8>,
+}
+
+impl Mask {
+    ///Reads mask from image in the same formats as sprites.
+    pub fn load(path: &str) -> Result<Self, MaskError> {
+        let bytes = fs::read(path).map_err(MaskError::Io)?;
+        let metadata = amethyst::renderer::TextureMetadata::srgb();
+        let data = match ImageFormat::from_path(path) {
+            Some(ImageFormat::Png) => amethyst::renderer::PngFormat.import(bytes, metadata),
+            Some(ImageFormat::Jpg) => amethyst::renderer::JpgFormat.import(bytes, metadata),
+            None => return Err(MaskError::UnknownFormat),
+        };
+
+        let image = match data {
+            Ok(amethyst::renderer::TextureData::Image(image, _)) => image.rgba,
+            Ok(_) => unreach!(),
+            Err(error) => return Err(MaskError::Format(error.to_string())),
+        };
+
+        //Nearest neighbour is enough, as mask is stretched over screen anyway
+        let (src_width, src_height) = (image.width(), image.height());
+        let scale = (src_width.max(src_height) as f32 / MASK_SIZE_LIMIT as f32).max(1.0);
+        let width = ((src_width as f32 / scale) as u32).max(1);
+        let height = ((src_height as f32 / scale) as u32).max(1);
+
+        let mut luminance = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = image.get_pixel(x * src_width / width, y * src_height / height);
+                let value = 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32;
+                luminance.push(value.round() as u8);
             }
         }
+
+        Ok(Self {
+            width,
+            height,
+            luminance,
+        })
     }
 }
 
@@ -0,0 +1,57 @@
+//!Input helpers
+
+use amethyst::prelude::World;
+use amethyst::winit::{Event, WindowEvent, MouseScrollDelta, KeyboardInput, ElementState, VirtualKeyCode, MouseButton};
+
+use crate::game::components::camera::Viewport;
+
+///Returns vertical mouse wheel movement, positive when scrolled up.
+pub fn mouse_wheel(event: &Event) -> Option<f32> {
+    match event {
+        Event::WindowEvent { event: WindowEvent::MouseWheel { delta, .. }, .. } => match delta {
+            MouseScrollDelta::LineDelta(_, y) => Some(*y),
+            MouseScrollDelta::PixelDelta(position) => Some(position.y as f32),
+        },
+        _ => None,
+    }
+}
+
+///Returns whether Alt+Enter or F11 is pressed to toggle fullscreen.
+pub fn is_fullscreen_toggle(event: &Event) -> bool {
+    match event {
+        Event::WindowEvent { event: WindowEvent::KeyboardInput { input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(key), modifiers, .. }, .. }, .. } => match key {
+            VirtualKeyCode::F11 => true,
+            VirtualKeyCode::Return => modifiers.alt,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+///Returns position of cursor on stage in virtual pixels.
+///
+///Returns `None` when cursor is outside of window or over letterbox bars.
+pub fn cursor_on_stage(world: &World) -> Option<(f32, f32)> {
+    let input = world.read_resource::<amethyst::input::InputHandler<String, String>>();
+    let screen = world.read_resource::<amethyst::renderer::ScreenDimensions>();
+    let (x, y) = input.mouse_position()?;
+
+    Viewport::from_screen(&screen).to_stage((x as f32, y as f32), (screen.width(), screen.height()))
+}
+
+///Returns position of cursor on screen while mouse `button` is held.
+pub fn held_cursor(world: &World, button: MouseButton) -> Option<(f32, f32)> {
+    let input = world.read_resource::<amethyst::input::InputHandler<String, String>>();
+    if !input.mouse_button_is_down(button) {
+        return None;
+    }
+
+    input.mouse_position().map(|(x, y)| (x as f32, y as f32))
+}
+
+///Returns whether mouse `button` is pressed over stage.
+///
+///Clicks on letterbox bars are ignored.
+pub fn is_stage_click(event: &Event, button: MouseButton, world: &World) -> bool {
+    amethyst::input::is_mouse_button_down(event, button) && cursor_on_stage(world).is_some()
+}
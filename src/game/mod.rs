@@ -1,6 +1,11 @@
 mod config;
+mod input;
 mod graphics;
 mod components;
+mod save;
+mod settings;
+mod audio;
+mod script;
 mod state;
 mod systems;
 
@@ -9,6 +14,8 @@ const ASSETS_DIR: &'static str = "./";
 use self::state::{GameDataBuilder, Base, Adv};
 
 pub fn run() -> amethyst::Result<()> {
+    let settings = settings::Settings::load();
+
     let sprite_pass = amethyst::renderer::DrawFlat2D::new().with_transparency(amethyst::renderer::ColorMask::all(), amethyst::renderer::ALPHA, None);
     //Clear screen with black
     //clear_target takes RGB colour
@@ -17,20 +24,44 @@ pub fn run() -> amethyst::Result<()> {
                                                            .with_pass(sprite_pass)
                                                            .with_pass(amethyst::ui::DrawUi::new());
     let pipe = amethyst::renderer::Pipeline::build().with_stage(pipe);
-    let pipe = amethyst::renderer::RenderBundle::new(pipe, Some(config::get_display()));
+    let pipe = amethyst::renderer::RenderBundle::new(pipe, Some(config::get_display(&settings)));
 
     let game_data = GameDataBuilder::default().with_bundle(Base, pipe.with_sprite_sheet_processor()).expect("To add bundle")
                                               .with_bundle(Base, amethyst::core::transform::bundle::TransformBundle::new()).expect("To add bundle")
                                               .with_bundle(Base, amethyst::input::InputBundle::<String, String>::new()).expect("To add bundle")
                                               .with_bundle(Base, amethyst::ui::UiBundle::<String, String>::new()).expect("To add bundle")
                                               .with(Base, amethyst::ui::UiMouseSystem::<String, String>::new(), "ui_mouse", &[])
-                                              .with(Adv, systems::Demo::default(), systems::demo::NAME, &[])
+                                              .with(Base, systems::Transition::default(), systems::transition::NAME, &[])
+                                              .with(Base, systems::Audio::default(), systems::audio::NAME, &[])
+                                              .with(Base, systems::Viewport::default(), systems::viewport::NAME, &[])
+                                              .with(Base, systems::Window::default(), systems::window::NAME, &[])
+                                              .with(Adv, systems::Typewriter::default(), systems::typewriter::NAME, &[])
+                                              .with(Adv, systems::Fit::default(), systems::fit::NAME, &[])
+                                              .with(Adv, systems::Tween::default(), systems::tween::NAME, &[systems::fit::NAME])
                                               .with(Adv, amethyst::renderer::HideHierarchySystem::default(), "hide_hier", &[]);
 
-    amethyst::Application::build(ASSETS_DIR, state::Menu::default()).expect("Create application builder")
-                                                                    .build(game_data)
-                                                                    .expect("Build application")
-                                                                    .run();
+    //Without sound device game continues silently
+    let output = amethyst::audio::output::default_output();
+
+    let mut app = amethyst::Application::build(ASSETS_DIR, state::Menu::default()).expect("Create application builder")
+                                                                                  .register::<components::scope::Owner>()
+                                                                                  .register::<components::scope::Persistent>()
+                                                                                  .with_resource(components::scope::Scopes::default())
+                                                                                  .with_resource(save::Pending::default())
+                                                                                  .with_resource(components::transition::Rules::default())
+                                                                                  .with_resource(components::typewriter::TextSpeed(settings.text_speed))
+                                                                                  .with_resource(components::typewriter::AutoDelay(settings.auto_delay))
+                                                                                  .with_resource(save::Seen::load())
+                                                                                  .with_resource(save::SkipUnread(settings.skip_unread))
+                                                                                  .with_resource(settings.audio(output.clone()))
+                                                                                  .with_resource(audio::StopVoice(settings.stop_voice))
+                                                                                  .with_resource(settings);
+
+    if let Some(output) = output {
+        app = app.with_resource(output);
+    }
+
+    app.build(game_data).expect("Build application").run();
 
 
     Ok(())
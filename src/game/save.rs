@@ -0,0 +1,381 @@
+//!Save data
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs, io};
+
+use crate::game::script::{Position, Variables, History, LineId, StagePosition, BackgroundFit};
+
+///Current version of save format.
+///
+///Must be increased on any incompatible change to `Save`
+pub const VERSION: u32 = 1;
+///Slot used by quick save.
+pub const QUICK_SLOT: u32 = 0;
+///Number of slots shown on single page of save/load screen.
+pub const SLOTS_PER_PAGE: u32 = 6;
+///Number of pages on save/load screen.
+pub const PAGES: u32 = 5;
+
+pub const APP_DIR: &'static str = "vn";
+const SAVE_DIR: &'static str = "saves";
+const SEEN_FILE: &'static str = "seen.ron";
+
+///Returns directory with user data.
+pub fn data_dir() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join(APP_DIR),
+        None => PathBuf::from(APP_DIR),
+    }
+}
+
+///Returns path to file of save slot.
+pub fn slot_path(slot: u32) -> PathBuf {
+    data_dir().join(SAVE_DIR).join(format!("slot_{}.ron", slot))
+}
+
+///Character visible on stage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Character {
+    pub name: String,
+    pub expression: Option<String>,
+    #[serde(default)]
+    pub position: StagePosition,
+}
+
+///Snapshot of playthrough.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Save {
+    pub version: u32,
+    ///Seconds since UNIX epoch.
+    pub timestamp: u64,
+    ///Path to script.
+    pub script: String,
+    ///Position of currently displayed line or choice.
+    pub position: Position,
+    ///Name of current chapter.
+    pub chapter: Option<String>,
+    ///Text of currently displayed line.
+    pub preview: String,
+    pub variables: Variables,
+    pub characters: Vec<Character>,
+    pub background: Option<String>,
+    #[serde(default)]
+    pub background_fit: BackgroundFit,
+    ///Music that is playing, it is restored from the beginning.
+    #[serde(default)]
+    pub bgm: Option<String>,
+    pub text_hidden: bool,
+    ///Dialogue history, including currently displayed line.
+    #[serde(default)]
+    pub history: History,
+}
+
+//Used to check version before parsing whole save.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Serialize(ron::ser::Error),
+    Deserialize(PathBuf, ron::de::Error),
+    Version {
+        path: PathBuf,
+        found: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            Error::Serialize(error) => write!(fmt, "unable to serialize save: {}", error),
+            Error::Deserialize(path, error) => write!(fmt, "{}: invalid save: {}", path.display(), error),
+            Error::Version { path, found } => write!(fmt, "{}: save version {} is not supported, expected {}", path.display(), found, VERSION),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+///Save picked on load screen.
+///
+///It is started by the state that is beneath load screen.
+#[derive(Default)]
+pub struct Pending(pub Option<Save>);
+
+///Dialogue lines that player has seen in any playthrough.
+///
+///Stored as resource and kept in its own file, independently of save slots.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Seen {
+    lines: HashSet<LineId>,
+}
+
+impl Seen {
+    ///Returns path to file with seen lines.
+    pub fn path() -> PathBuf {
+        data_dir().join(SEEN_FILE)
+    }
+
+    ///Loads seen lines, starting anew if file cannot be read.
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    ///Loads seen lines from `path`, starting anew if it cannot be read.
+    pub fn load_from(path: &Path) -> Self {
+        let path = path.to_path_buf();
+
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("{}", Error::Io(path, error));
+                return Self::default();
+            }
+        };
+
+        match ron::de::from_str(&data) {
+            Ok(seen) => seen,
+            Err(error) => {
+                warn!("{}", Error::Deserialize(path, error));
+                Self::default()
+            }
+        }
+    }
+
+    ///Writes seen lines into file.
+    pub fn store(&self) -> Result<(), Error> {
+        self.store_to(&Self::path())
+    }
+
+    ///Writes seen lines into `path`.
+    pub fn store_to(&self, path: &Path) -> Result<(), Error> {
+        let path = path.to_path_buf();
+        let data = ron::ser::to_string(self).map_err(Error::Serialize)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| Error::Io(dir.to_path_buf(), error))?;
+        }
+
+        fs::write(&path, data).map_err(|error| Error::Io(path.clone(), error))
+    }
+
+    ///Marks line as seen.
+    ///
+    ///Returns whether line was seen before.
+    pub fn insert(&mut self, line: LineId) -> bool {
+        !self.lines.insert(line)
+    }
+}
+
+///Whether skip mode continues through lines that are not seen yet.
+#[derive(Default)]
+pub struct SkipUnread(pub bool);
+
+///Formats timestamp as UTC date `YYYY-MM-DD HH:MM`
+pub fn format_timestamp(timestamp: u64) -> String {
+    const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+    let days = timestamp / SECS_PER_DAY;
+    let secs = timestamp % SECS_PER_DAY;
+
+    //Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    //Timestamp is never before epoch, so all values are positive
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_idx = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_idx + 2) / 5 + 1;
+    let month = if month_idx < 10 { month_idx + 3 } else { month_idx - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, secs / 3600, secs % 3600 / 60)
+}
+
+impl Save {
+    ///Returns current time as timestamp.
+    pub fn now() -> u64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs(),
+            Err(_) => 0,
+        }
+    }
+
+    ///Serializes save into text of save file.
+    pub fn serialize(&self) -> Result<String, Error> {
+        ron::ser::to_string_pretty(self, Default::default()).map_err(Error::Serialize)
+    }
+
+    ///Deserializes save from text of file at `path`, which is used in errors.
+    ///
+    ///Version is checked first, so that save of other version is not parsed as current one.
+    pub fn deserialize(data: &str, path: &Path) -> Result<Self, Error> {
+        let header: Header = ron::de::from_str(data).map_err(|error| Error::Deserialize(path.to_path_buf(), error))?;
+        if header.version != VERSION {
+            return Err(Error::Version {
+                path: path.to_path_buf(),
+                found: header.version,
+            });
+        }
+
+        ron::de::from_str(data).map_err(|error| Error::Deserialize(path.to_path_buf(), error))
+    }
+
+    ///Writes save into file.
+    pub fn write_to(&self, path: &Path) -> Result<(), Error> {
+        let data = self.serialize()?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| Error::Io(dir.to_path_buf(), error))?;
+        }
+
+        fs::write(path, data).map_err(|error| Error::Io(path.to_path_buf(), error))
+    }
+
+    ///Reads save from file.
+    pub fn read_from(path: &Path) -> Result<Self, Error> {
+        let data = fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+        Self::deserialize(&data, path)
+    }
+
+    ///Writes save into slot.
+    pub fn write(&self, slot: u32) -> Result<(), Error> {
+        self.write_to(&slot_path(slot))
+    }
+
+    ///Reads save from slot.
+    pub fn read(slot: u32) -> Result<Self, Error> {
+        Self::read_from(&slot_path(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::script::{Value, HistoryEntry};
+
+    fn save() -> Save {
+        let mut variables = Variables::default();
+        variables.set("affection", Value::Int(2));
+        let mut history = History::default();
+        history.push(HistoryEntry::Line { speaker: Some("kaoru".to_owned()), text: "Hello".to_owned() });
+
+        Save {
+            version: VERSION,
+            timestamp: 1_500_000_000,
+            script: "assets/script/main.vns".to_owned(),
+            position: 3,
+            chapter: Some("Prologue".to_owned()),
+            preview: "kaoru: Hello".to_owned(),
+            variables,
+            characters: vec![Character {
+                name: "kaoru".to_owned(),
+                expression: Some("smile".to_owned()),
+                position: StagePosition::Left,
+            }],
+            background: Some("room".to_owned()),
+            background_fit: BackgroundFit::Contain,
+            bgm: Some("evening.ogg".to_owned()),
+            text_hidden: true,
+            history,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = save();
+        let data = save.serialize().expect("To serialize save");
+        let restored = Save::deserialize(&data, Path::new("test")).expect("To deserialize save");
+
+        assert_eq!(restored.version, VERSION);
+        assert_eq!(restored.timestamp, save.timestamp);
+        assert_eq!(restored.script, save.script);
+        assert_eq!(restored.position, save.position);
+        assert_eq!(restored.chapter, save.chapter);
+        assert_eq!(restored.preview, save.preview);
+        assert_eq!(restored.variables.get("affection"), Some(&Value::Int(2)));
+        assert_eq!(restored.characters, save.characters);
+        assert_eq!(restored.background, save.background);
+        assert_eq!(restored.background_fit, save.background_fit);
+        assert_eq!(restored.bgm, save.bgm);
+        assert_eq!(restored.text_hidden, save.text_hidden);
+        assert_eq!(restored.history.iter().collect::<Vec<_>>(), save.history.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reject_other_version() {
+        let mut save = save();
+        save.version = VERSION + 1;
+        let data = save.serialize().expect("To serialize save");
+
+        match Save::deserialize(&data, Path::new("test")) {
+            Err(Error::Version { found, .. }) => assert_eq!(found, VERSION + 1),
+            Err(error) => panic!("Unexpected error: {}", error),
+            Ok(_) => panic!("Save of other version is loaded"),
+        }
+    }
+
+    #[test]
+    fn load_save_without_optional_fields() {
+        let data = format!(r#"(
+            version: {},
+            timestamp: 0,
+            script: "assets/script/main.vns",
+            position: 3,
+            chapter: None,
+            preview: "Hello",
+            variables: (inner: {{}}),
+            characters: [(name: "kaoru", expression: None)],
+            background: Some("room"),
+            text_hidden: false,
+        )"#, VERSION);
+        let save = Save::deserialize(&data, Path::new("test")).expect("To deserialize save");
+
+        assert_eq!(save.position, 3);
+        assert_eq!(save.characters[0].position, StagePosition::default());
+        assert_eq!(save.background_fit, BackgroundFit::default());
+        assert_eq!(save.bgm, None);
+        assert!(save.history.is_empty());
+    }
+
+    #[test]
+    fn store_seen_lines() {
+        let path = std::env::temp_dir().join(format!("vn_seen_{}.ron", std::process::id()));
+
+        let mut seen = Seen::default();
+        assert!(!seen.insert(1));
+        assert!(seen.insert(1));
+        seen.insert(u64::max_value());
+        seen.store_to(&path).expect("To store seen lines");
+
+        let mut restored = Seen::load_from(&path);
+        let _ = fs::remove_file(&path);
+        assert!(restored.insert(1));
+        assert!(restored.insert(u64::max_value()));
+        assert!(!restored.insert(2));
+
+        //Without file nothing is seen
+        let mut missing = Seen::load_from(&path);
+        assert!(!missing.insert(1));
+    }
+
+    #[test]
+    fn format_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_timestamp(1_582_977_599), "2020-02-29 11:59");
+        assert_eq!(format_timestamp(1_709_251_199), "2024-02-29 23:59");
+        assert_eq!(format_timestamp(1_709_251_200), "2024-03-01 00:00");
+        assert_eq!(format_timestamp(1_500_000_000), "2017-07-14 02:40");
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use super::variables::{Value, Variables};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    And,
+    Or,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEq => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEq => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Value(Value),
+    Var(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    UndefinedVariable(String),
+    InvalidOperand {
+        op: &'static str,
+        found: &'static str,
+    },
+    InvalidOperands {
+        op: &'static str,
+        left: &'static str,
+        right: &'static str,
+    },
+    ExpectedBool(&'static str),
+    DivisionByZero,
+    Overflow,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::UndefinedVariable(name) => write!(fmt, "variable '{}' is not defined", name),
+            EvalError::InvalidOperand { op, found } => write!(fmt, "cannot apply '{}' to {}", op, found),
+            EvalError::InvalidOperands { op, left, right } => write!(fmt, "cannot apply '{}' to {} and {}", op, left, right),
+            EvalError::ExpectedBool(found) => write!(fmt, "condition must be bool, but it is {}", found),
+            EvalError::DivisionByZero => write!(fmt, "division by zero"),
+            EvalError::Overflow => write!(fmt, "integer overflow"),
+        }
+    }
+}
+
+impl Expr {
+    pub fn eval(&self, vars: &Variables) -> Result<Value, EvalError> {
+        match self {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => vars.get(name).cloned().ok_or_else(|| EvalError::UndefinedVariable(name.clone())),
+            Expr::Unary(op, expr) => match (op, expr.eval(vars)?) {
+                (UnaryOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+                (UnaryOp::Neg, Value::Int(value)) => value.checked_neg().map(Value::Int).ok_or(EvalError::Overflow),
+                (UnaryOp::Not, value) => Err(EvalError::InvalidOperand { op: "not", found: value.type_name() }),
+                (UnaryOp::Neg, value) => Err(EvalError::InvalidOperand { op: "-", found: value.type_name() }),
+            },
+            //Short-circuit evaluation
+            Expr::Binary(BinaryOp::And, left, right) => if left.eval_bool(vars)? {
+                right.eval_bool(vars).map(Value::Bool)
+            } else {
+                Ok(Value::Bool(false))
+            },
+            Expr::Binary(BinaryOp::Or, left, right) => if left.eval_bool(vars)? {
+                Ok(Value::Bool(true))
+            } else {
+                right.eval_bool(vars).map(Value::Bool)
+            },
+            Expr::Binary(op, left, right) => Self::binary(*op, left.eval(vars)?, right.eval(vars)?),
+        }
+    }
+
+    ///Evaluates expression that must result in bool.
+    pub fn eval_bool(&self, vars: &Variables) -> Result<bool, EvalError> {
+        match self.eval(vars)? {
+            Value::Bool(value) => Ok(value),
+            value => Err(EvalError::ExpectedBool(value.type_name())),
+        }
+    }
+
+    fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, EvalError> {
+        let result = match (op, &left, &right) {
+            (BinaryOp::Eq, _, _) if left.type_name() == right.type_name() => Value::Bool(left == right),
+            (BinaryOp::NotEq, _, _) if left.type_name() == right.type_name() => Value::Bool(left != right),
+            (BinaryOp::Less, Value::Int(left), Value::Int(right)) => Value::Bool(left < right),
+            (BinaryOp::LessEq, Value::Int(left), Value::Int(right)) => Value::Bool(left <= right),
+            (BinaryOp::Greater, Value::Int(left), Value::Int(right)) => Value::Bool(left > right),
+            (BinaryOp::GreaterEq, Value::Int(left), Value::Int(right)) => Value::Bool(left >= right),
+            (BinaryOp::Add, Value::Int(left), Value::Int(right)) => Value::Int(left.checked_add(*right).ok_or(EvalError::Overflow)?),
+            (BinaryOp::Add, Value::Str(left), Value::Str(right)) => Value::Str(format!("{}{}", left, right)),
+            (BinaryOp::Sub, Value::Int(left), Value::Int(right)) => Value::Int(left.checked_sub(*right).ok_or(EvalError::Overflow)?),
+            (BinaryOp::Mul, Value::Int(left), Value::Int(right)) => Value::Int(left.checked_mul(*right).ok_or(EvalError::Overflow)?),
+            (BinaryOp::Div, Value::Int(_), Value::Int(0)) | (BinaryOp::Rem, Value::Int(_), Value::Int(0)) => return Err(EvalError::DivisionByZero),
+            (BinaryOp::Div, Value::Int(left), Value::Int(right)) => Value::Int(left.checked_div(*right).ok_or(EvalError::Overflow)?),
+            (BinaryOp::Rem, Value::Int(left), Value::Int(right)) => Value::Int(left.checked_rem(*right).ok_or(EvalError::Overflow)?),
+            _ => return Err(EvalError::InvalidOperands {
+                op: op.as_str(),
+                left: left.type_name(),
+                right: right.type_name(),
+            }),
+        };
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::script::{parse, Command};
+
+    fn eval(source: &str, vars: &Variables) -> Result<Value, EvalError> {
+        let script = parse("test", &format!("set result = {}", source)).expect("To parse expression");
+
+        match &script.statements[0].command {
+            Command::Set { value, .. } => value.eval(vars),
+            _ => unreach!(),
+        }
+    }
+
+    #[test]
+    fn eval_arithmetic() {
+        let vars = Variables::default();
+
+        assert_eq!(eval("1 + 2 * 3", &vars), Ok(Value::Int(7)));
+        assert_eq!(eval("(1 + 2) * 3", &vars), Ok(Value::Int(9)));
+        assert_eq!(eval("10 - 4 - 3", &vars), Ok(Value::Int(3)));
+        assert_eq!(eval("-7 / 2", &vars), Ok(Value::Int(-3)));
+        assert_eq!(eval("-7 % 3", &vars), Ok(Value::Int(-1)));
+        assert_eq!(eval("\"Kao\" + \"ru\"", &vars), Ok(Value::Str("Kaoru".to_owned())));
+    }
+
+    #[test]
+    fn eval_conditions() {
+        let mut vars = Variables::default();
+        vars.set("affection", Value::Int(2));
+        vars.set("met", Value::Bool(true));
+        vars.set("name", Value::Str("Kaoru".to_owned()));
+
+        assert_eq!(eval("affection >= 2 and met", &vars), Ok(Value::Bool(true)));
+        assert_eq!(eval("not met or affection < 0", &vars), Ok(Value::Bool(false)));
+        assert_eq!(eval("name == \"Kaoru\"", &vars), Ok(Value::Bool(true)));
+        assert_eq!(eval("affection != 2", &vars), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn eval_short_circuit() {
+        let vars = Variables::default();
+
+        assert_eq!(eval("false and unknown", &vars), Ok(Value::Bool(false)));
+        assert_eq!(eval("true or unknown", &vars), Ok(Value::Bool(true)));
+        assert_eq!(eval("true and unknown", &vars), Err(EvalError::UndefinedVariable("unknown".to_owned())));
+    }
+
+    #[test]
+    fn eval_overflow() {
+        let vars = Variables::default();
+
+        assert_eq!(eval("9223372036854775807 + 1", &vars), Err(EvalError::Overflow));
+        assert_eq!(eval("-9223372036854775807 - 1", &vars), Ok(Value::Int(std::i64::MIN)));
+        assert_eq!(eval("-9223372036854775807 - 2", &vars), Err(EvalError::Overflow));
+        assert_eq!(eval("4611686018427387904 * 2", &vars), Err(EvalError::Overflow));
+        assert_eq!(eval("-(-9223372036854775807 - 1)", &vars), Err(EvalError::Overflow));
+        assert_eq!(eval("(-9223372036854775807 - 1) / -1", &vars), Err(EvalError::Overflow));
+        assert_eq!(eval("1 / 0", &vars), Err(EvalError::DivisionByZero));
+        assert_eq!(eval("1 % 0", &vars), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn eval_type_errors() {
+        let vars = Variables::default();
+
+        assert_eq!(eval("1 + true", &vars), Err(EvalError::InvalidOperands { op: "+", left: "integer", right: "bool" }));
+        assert_eq!(eval("1 == \"1\"", &vars), Err(EvalError::InvalidOperands { op: "==", left: "integer", right: "string" }));
+        assert_eq!(eval("not 1", &vars), Err(EvalError::InvalidOperand { op: "not", found: "integer" }));
+        assert_eq!(Expr::Value(Value::Int(1)).eval_bool(&vars), Err(EvalError::ExpectedBool("integer")));
+    }
+}
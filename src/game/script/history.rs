@@ -0,0 +1,59 @@
+use serde::{Serialize, Deserialize};
+
+use std::collections::VecDeque;
+
+///Maximum number of entries kept in history.
+pub const LIMIT: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Entry {
+    ///Displayed dialogue line.
+    Line {
+        speaker: Option<String>,
+        text: String,
+    },
+    ///Text of chosen option.
+    Choice(String),
+}
+
+impl Entry {
+    ///Returns text as it is shown in backlog.
+    pub fn display(&self) -> String {
+        match self {
+            Entry::Line { speaker: Some(speaker), text } => format!("{}: {}", speaker, text),
+            Entry::Line { speaker: None, text } => text.clone(),
+            Entry::Choice(text) => format!("> {}", text),
+        }
+    }
+}
+
+///Dialogue history.
+///
+///Stored as resource, oldest entries are dropped once `LIMIT` is reached.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    entries: VecDeque<Entry>,
+}
+
+impl History {
+    pub fn push(&mut self, entry: Entry) {
+        if self.entries.len() >= LIMIT {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    ///Returns entries from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item=&Entry> {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,134 @@
+use super::parser::ErrorKind;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Float(f32),
+    Symbol(&'static str),
+}
+
+impl Token {
+    ///Returns human readable description of token for error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Ident(ident) => format!("'{}'", ident),
+            Token::Str(text) => format!("string \"{}\"", text),
+            Token::Int(num) => format!("number {}", num),
+            Token::Float(num) => format!("number {}", num),
+            Token::Symbol(sym) => format!("'{}'", sym),
+        }
+    }
+}
+
+///Token with its column in the line (starting from 1)
+#[derive(Debug, Clone)]
+pub struct Spanned {
+    pub token: Token,
+    pub column: usize,
+}
+
+//Longest symbols must go first
+const SYMBOLS: &[&'static str] = &[
+    "->", "==", "!=", "<=", ">=",
+    ":", "<", ">", "+", "-", "*", "/", "%", "(", ")", "=",
+];
+const COMMENT: char = '#';
+
+fn is_ident_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_'
+}
+
+fn is_ident(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+///Splits single line of script into tokens.
+///
+///On error returns column and kind of error.
+pub fn tokenize(line: &str) -> Result<Vec<Spanned>, (usize, ErrorKind)> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut result = Vec::new();
+    let mut idx = 0;
+
+    'outer: while idx < chars.len() {
+        let ch = chars[idx];
+        let column = idx + 1;
+
+        if ch.is_whitespace() {
+            idx += 1;
+            continue;
+        } else if ch == COMMENT {
+            break;
+        } else if ch == '"' {
+            let mut text = String::new();
+            idx += 1;
+
+            loop {
+                match chars.get(idx) {
+                    Some('"') => break,
+                    Some('\\') => {
+                        idx += 1;
+                        match chars.get(idx) {
+                            Some('n') => text.push('\n'),
+                            Some('"') => text.push('"'),
+                            Some('\\') => text.push('\\'),
+                            Some(other) => return Err((idx + 1, ErrorKind::InvalidEscape(*other))),
+                            None => return Err((column, ErrorKind::UnterminatedString)),
+                        }
+                    },
+                    Some(other) => text.push(*other),
+                    None => return Err((column, ErrorKind::UnterminatedString)),
+                }
+                idx += 1;
+            }
+
+            idx += 1;
+            result.push(Spanned { token: Token::Str(text), column });
+        } else if ch.is_ascii_digit() {
+            let start = idx;
+            while idx < chars.len() && chars[idx].is_ascii_digit() {
+                idx += 1;
+            }
+
+            let is_float = chars.get(idx) == Some(&'.') && chars.get(idx + 1).map(|ch| ch.is_ascii_digit()).unwrap_or(false);
+            if is_float {
+                idx += 1;
+                while idx < chars.len() && chars[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+            }
+
+            let text = chars[start..idx].iter().collect::<String>();
+            let token = if is_float {
+                text.parse().map(Token::Float).map_err(|_| (column, ErrorKind::InvalidNumber(text.clone())))?
+            } else {
+                text.parse().map(Token::Int).map_err(|_| (column, ErrorKind::InvalidNumber(text.clone())))?
+            };
+
+            result.push(Spanned { token, column });
+        } else if is_ident_start(ch) {
+            let start = idx;
+            while idx < chars.len() && is_ident(chars[idx]) {
+                idx += 1;
+            }
+
+            let token = Token::Ident(chars[start..idx].iter().collect());
+            result.push(Spanned { token, column });
+        } else {
+            for symbol in SYMBOLS {
+                let len = symbol.chars().count();
+                if chars[idx..].iter().take(len).cloned().eq(symbol.chars()) {
+                    idx += len;
+                    result.push(Spanned { token: Token::Symbol(symbol), column });
+                    continue 'outer;
+                }
+            }
+
+            return Err((column, ErrorKind::UnexpectedChar(ch)));
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,370 @@
+//!Story script
+//!
+//!Script is a plain text file where each non-empty line is a single command:
+//!
+//!```text
+//!# Comment
+//!chapter "Prologue"
+//!label start:
+//!bg menu
+//!show kaoru
+//!show kaoru smile at left
+//!move kaoru to far-right
+//!move kaoru to -300 over 0.5 ease-out
+//!wait
+//!bg menu with fade 1.0
+//!hide kaoru with dissolve
+//!play bgm "evening.ogg" fade 2.0
+//!play sfx "door.wav"
+//!stop bgm fade 1.0
+//!volume ambient 0.5
+//!kaoru "Dialogue line with speaker"
+//!"Narration line"
+//!"Slowly revealed line" speed 10
+//!kaoru "Voiced line" voice "kaoru_001.ogg"
+//!hide kaoru
+//!flag met_kaoru
+//!set name = "Kaoru"
+//!inc affection by 2
+//!if affection >= 2 and met_kaoru
+//!"She smiles."
+//!elif not met_kaoru
+//!"Who is she?"
+//!else
+//!"She looks away."
+//!end
+//!choice
+//!"Follow her" -> follow if met_kaoru
+//!"Stay" -> stay enable affection < 5
+//!end
+//!jump start
+//!```
+//!
+//!Variables can be bool, integer or string. `flag name` is the same as `set name = true`.
+//!`inc` and `dec` of variable that is not set yet start from 0.
+//!Expressions are integer-only: fractional numbers like `0.5` are accepted only by stage commands.
+//!
+//!Stage positions are `far-left`, `left`, `center`, `right`, `far-right`
+//!or number, which is offset from center of stage in pixels of its 1920×1080 virtual resolution.
+//!Movement takes `over` seconds with easing `linear`, `ease-in`, `ease-out`, `ease-in-out`, `bounce` or `elastic`.
+//!`wait` pauses script until all movements are finished.
+//!
+//!`bg`, `show` and `hide` can be done `with` transition and optional duration in seconds:
+//!`fade`, `fade-white`, `dissolve`, `wipe-left`, `wipe-right`, `wipe-up` or `wipe-down`.
+//!Dissolve crossfades sprites, while the rest cover whole screen before change.
+//!
+//!`bg NAME` can be scaled to the stage with `cover` (default), `contain` or `stretch`: `bg room contain with dissolve`.
+//!`bg clear` removes background.
+//!
+//!Audio channels are `bgm`, `sfx`, `voice` and `ambient`, files are taken from `assets/audio`.
+//!Music and ambient sounds are looped unless `once` is specified, while the rest are looped only with `loop`.
+//!Playing music with `fade` crossfades it with current one.
+//!Voice of dialogue line is played on `voice` channel.
+//!
+//!Rule transition covers screen following luminance of grayscale image `assets/rules/{name}.png`,
+//!with optional softness of its edge from 0 to 1: `bg room with rule "circle" 1.0 soft 0.2`.
+//!
+//!Choice option is hidden when its `if` condition is false,
+//!and shown as disabled when its `enable` condition is false.
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::HashMap;
+
+use crate::game::components::tween::Easing;
+use crate::game::components::transition;
+use crate::game::audio::Channel;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+mod lexer;
+mod parser;
+mod runner;
+mod expr;
+mod variables;
+mod history;
+
+pub use self::parser::{parse, ParseError};
+pub use self::runner::{Runner, Line, Choice, RuntimeError};
+pub use self::expr::{Expr, EvalError};
+pub use self::variables::{Variables, Value};
+pub use self::history::{History, Entry as HistoryEntry};
+
+///Script that is started on new game.
+pub const MAIN: &'static str = "assets/scripts/main.vns";
+
+///Index of statement within script
+pub type Position = usize;
+
+///Identifier of dialogue line.
+///
+///It depends only on script name, speaker and text, so it remains the same when script is edited around the line.
+pub type LineId = u64;
+
+///Computes identifier of dialogue line, using FNV-1a hash.
+pub fn line_id(script: &str, speaker: Option<&str>, text: &str) -> LineId {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let speaker = speaker.unwrap_or("");
+    //0xff never occurs in UTF-8, so it separates parts unambiguously
+    let bytes = script.bytes().chain(Some(0xff)).chain(speaker.bytes()).chain(Some(0xff)).chain(text.bytes());
+
+    bytes.fold(OFFSET, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+impl Command {
+    ///Returns transition that is used by command.
+    pub fn transition(&self) -> Option<Transition> {
+        match self {
+            Command::Show { transition, .. } | Command::Hide { transition, .. } | Command::Background { transition, .. } => transition.clone(),
+            _ => None,
+        }
+    }
+}
+
+///Horizontal position of character on stage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StagePosition {
+    FarLeft,
+    Left,
+    Center,
+    Right,
+    FarRight,
+    ///Offset from center of stage.
+    Offset(f32),
+}
+
+impl Default for StagePosition {
+    fn default() -> Self {
+        StagePosition::Center
+    }
+}
+
+impl StagePosition {
+    ///Returns position by its name in script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "far-left" => Some(StagePosition::FarLeft),
+            "left" => Some(StagePosition::Left),
+            "center" => Some(StagePosition::Center),
+            "right" => Some(StagePosition::Right),
+            "far-right" => Some(StagePosition::FarRight),
+            _ => None,
+        }
+    }
+
+    ///Returns x coordinate on stage of `width`.
+    pub fn x(self, width: f32) -> f32 {
+        match self {
+            StagePosition::FarLeft => width * 0.1,
+            StagePosition::Left => width * 0.3,
+            StagePosition::Center => width * 0.5,
+            StagePosition::Right => width * 0.7,
+            StagePosition::FarRight => width * 0.9,
+            StagePosition::Offset(offset) => width * 0.5 + offset,
+        }
+    }
+}
+
+///Scaling of background to the stage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BackgroundFit {
+    ///Fills whole stage, cropping parts of image that don't fit.
+    Cover,
+    ///Fits whole image into stage, leaving empty borders.
+    Contain,
+    ///Fills whole stage, ignoring aspect ratio of image.
+    Stretch,
+}
+
+impl Default for BackgroundFit {
+    fn default() -> Self {
+        BackgroundFit::Cover
+    }
+}
+
+impl BackgroundFit {
+    ///Returns fit by its name in script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cover" => Some(BackgroundFit::Cover),
+            "contain" => Some(BackgroundFit::Contain),
+            "stretch" => Some(BackgroundFit::Stretch),
+            _ => None,
+        }
+    }
+
+    ///Returns scale of image `width`x`height` that fits it onto stage `stage_width`x`stage_height`.
+    pub fn scale(self, width: f32, height: f32, stage_width: f32, stage_height: f32) -> (f32, f32) {
+        let (x, y) = (stage_width / width, stage_height / height);
+
+        match self {
+            BackgroundFit::Cover => (x.max(y), x.max(y)),
+            BackgroundFit::Contain => (x.min(y), x.min(y)),
+            BackgroundFit::Stretch => (x, y),
+        }
+    }
+}
+
+///Animated movement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    ///Duration in seconds.
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+///Transition that is used to change scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub kind: transition::Kind,
+    ///Duration in seconds.
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    ///Marks jump target.
+    Label(String),
+    ///Sets name of chapter, shown in saves.
+    Chapter(String),
+    ///Dialogue line, narration if there is no speaker.
+    Say {
+        speaker: Option<String>,
+        text: String,
+        ///Text speed override in characters per second
+        speed: Option<f32>,
+        ///Voice clip in `assets/audio`.
+        voice: Option<String>,
+    },
+    ///Shows character's sprite.
+    Show {
+        name: String,
+        expression: Option<String>,
+        ///Keeps current or default position when not specified.
+        position: Option<StagePosition>,
+        transition: Option<Transition>,
+    },
+    ///Moves character's sprite.
+    Move {
+        name: String,
+        position: StagePosition,
+        ///Moves instantly when not specified.
+        motion: Option<Motion>,
+    },
+    ///Pauses script until animations are finished.
+    Wait,
+    ///Hides character's sprite.
+    Hide {
+        name: String,
+        transition: Option<Transition>,
+    },
+    ///Changes background, or removes it if there is no name.
+    Background {
+        name: Option<String>,
+        fit: BackgroundFit,
+        transition: Option<Transition>,
+    },
+    ///Plays sound `assets/audio/{file}` on channel.
+    Play {
+        channel: Channel,
+        file: String,
+        looping: bool,
+        ///Duration of fade in, or crossfade for music.
+        fade: Option<f32>,
+    },
+    ///Stops sounds of channel.
+    Stop {
+        channel: Channel,
+        fade: Option<f32>,
+    },
+    ///Sets volume of channel from 0 to 1.
+    Volume {
+        channel: Channel,
+        volume: f32,
+    },
+    ///Continues execution from label.
+    Jump(String),
+    ///Offers player to choose where to jump.
+    Choice(Vec<ChoiceOption>),
+    ///Assigns value to variable.
+    Set {
+        name: String,
+        value: Expr,
+    },
+    ///Adds to integer variable.
+    Increment {
+        name: String,
+        by: Expr,
+    },
+    ///Continues execution from `target` if condition is false.
+    ///
+    ///Generated from `if` and `elif`.
+    Branch {
+        condition: Expr,
+        target: Position,
+    },
+    ///Continues execution from `target`.
+    ///
+    ///Generated at the end of `if` branches.
+    Goto(Position),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoiceOption {
+    pub text: String,
+    ///Label to jump to.
+    pub target: String,
+    ///Option is visible only when condition holds.
+    pub condition: Option<Expr>,
+    ///Option can be chosen only when condition holds.
+    pub enabled: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    ///Line in script file, starting from 1
+    pub line: usize,
+    pub command: Command,
+}
+
+#[derive(Debug, Default)]
+pub struct Script {
+    pub name: String,
+    pub statements: Vec<Statement>,
+    labels: HashMap<String, Position>,
+}
+
+impl Script {
+    ///Loads and parses script file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let name = path.to_string_lossy();
+
+        let source = fs::read_to_string(path).map_err(|error| Error::Io(name.to_string(), error))?;
+        parse(&name, &source).map_err(Error::Parse)
+    }
+
+    ///Returns position of label, if it exists.
+    pub fn label(&self, name: &str) -> Option<Position> {
+        self.labels.get(name).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(String, io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(fmt, "{}: unable to read script: {}", path, error),
+            Error::Parse(error) => write!(fmt, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
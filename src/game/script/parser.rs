@@ -0,0 +1,1032 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::lexer::{self, Token, Spanned};
+use super::{Script, Statement, Command, ChoiceOption, Position, StagePosition, BackgroundFit, Motion, Transition};
+use crate::game::components::tween::Easing;
+use crate::game::components::transition;
+use crate::game::audio::Channel;
+use super::expr::{Expr, UnaryOp, BinaryOp};
+use super::variables::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnterminatedString,
+    InvalidEscape(char),
+    InvalidNumber(String),
+    UnexpectedChar(char),
+    UnexpectedToken {
+        expected: &'static str,
+        found: String,
+    },
+    UnexpectedEnd {
+        expected: &'static str,
+    },
+    UnknownCommand(String),
+    UnknownOption(String),
+    DuplicateLabel(String),
+    UndefinedLabel(String),
+    EmptyChoice,
+    UnclosedChoice,
+    UnclosedBlock,
+    UnmatchedBlock(&'static str),
+    ReservedWord(String),
+    UnknownPosition(String),
+    UnknownEasing(String),
+    UnknownTransition(String),
+    UnknownFit(String),
+    UnknownChannel(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnterminatedString => write!(fmt, "unterminated string"),
+            ErrorKind::InvalidEscape(ch) => write!(fmt, "invalid escape sequence '\\{}'", ch),
+            ErrorKind::InvalidNumber(num) => write!(fmt, "invalid number '{}'", num),
+            ErrorKind::UnexpectedChar(ch) => write!(fmt, "unexpected character '{}'", ch),
+            ErrorKind::UnexpectedToken { expected, found } => write!(fmt, "expected {}, found {}", expected, found),
+            ErrorKind::UnexpectedEnd { expected } => write!(fmt, "expected {}, found end of line", expected),
+            ErrorKind::UnknownCommand(cmd) => write!(fmt, "unknown command '{}'", cmd),
+            ErrorKind::UnknownOption(option) => write!(fmt, "unknown option '{}'", option),
+            ErrorKind::DuplicateLabel(label) => write!(fmt, "label '{}' is already defined", label),
+            ErrorKind::UndefinedLabel(label) => write!(fmt, "label '{}' is not defined", label),
+            ErrorKind::EmptyChoice => write!(fmt, "choice has no options"),
+            ErrorKind::UnclosedChoice => write!(fmt, "choice is not closed with 'end'"),
+            ErrorKind::UnclosedBlock => write!(fmt, "'if' is not closed with 'end'"),
+            ErrorKind::UnmatchedBlock(keyword) => write!(fmt, "'{}' without matching 'if'", keyword),
+            ErrorKind::ReservedWord(word) => write!(fmt, "'{}' is reserved word", word),
+            ErrorKind::UnknownPosition(name) => write!(fmt, "unknown stage position '{}'", name),
+            ErrorKind::UnknownEasing(name) => write!(fmt, "unknown easing '{}'", name),
+            ErrorKind::UnknownTransition(name) => write!(fmt, "unknown transition '{}'", name),
+            ErrorKind::UnknownFit(name) => write!(fmt, "unknown background fit '{}'", name),
+            ErrorKind::UnknownChannel(name) => write!(fmt, "unknown audio channel '{}'", name),
+        }
+    }
+}
+
+///Script parsing error.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}:{}:{}: {}", self.file, self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+///Cursor over tokens of single line.
+struct Line {
+    tokens: Vec<Spanned>,
+    idx: usize,
+    //Column right after last character, used to report unexpected end of line
+    end: usize,
+}
+
+type LineResult<T> = Result<T, (usize, ErrorKind)>;
+
+//Words that cannot be used as variable names
+const RESERVED: &[&'static str] = &["and", "or", "not", "true", "false"];
+//Words that cannot be used as speaker names
+const COMMANDS: &[&'static str] = &["label", "chapter", "show", "move", "hide", "bg", "play", "stop", "volume", "jump", "wait", "choice", "if", "elif", "else", "end", "flag", "set", "inc", "dec"];
+
+impl Line {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(|spanned| &spanned.token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.idx + offset).map(|spanned| &spanned.token)
+    }
+
+    ///Returns column of the current token
+    fn column(&self) -> usize {
+        self.tokens.get(self.idx).map(|spanned| spanned.column).unwrap_or(self.end)
+    }
+
+    fn next(&mut self, expected: &'static str) -> LineResult<Token> {
+        match self.tokens.get(self.idx) {
+            Some(spanned) => {
+                self.idx += 1;
+                Ok(spanned.token.clone())
+            },
+            None => Err((self.end, ErrorKind::UnexpectedEnd { expected })),
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &'static str) -> LineResult<T> {
+        match self.tokens.get(self.idx) {
+            Some(spanned) => Err((spanned.column, ErrorKind::UnexpectedToken { expected, found: spanned.token.describe() })),
+            None => Err((self.end, ErrorKind::UnexpectedEnd { expected })),
+        }
+    }
+
+    fn ident(&mut self, expected: &'static str) -> LineResult<String> {
+        match self.peek() {
+            Some(Token::Ident(_)) => match self.next(expected)? {
+                Token::Ident(ident) => Ok(ident),
+                _ => unreach!(),
+            },
+            _ => self.unexpected(expected),
+        }
+    }
+
+    fn opt_ident(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Ident(_)) => match self.next("identifier") {
+                Ok(Token::Ident(ident)) => Some(ident),
+                _ => unreach!(),
+            },
+            _ => None,
+        }
+    }
+
+    fn string(&mut self, expected: &'static str) -> LineResult<String> {
+        match self.peek() {
+            Some(Token::Str(_)) => match self.next(expected)? {
+                Token::Str(text) => Ok(text),
+                _ => unreach!(),
+            },
+            _ => self.unexpected(expected),
+        }
+    }
+
+    fn number(&mut self, expected: &'static str) -> LineResult<f32> {
+        match self.peek() {
+            Some(Token::Int(num)) => {
+                let num = *num as f32;
+                self.idx += 1;
+                Ok(num)
+            },
+            Some(Token::Float(num)) => {
+                let num = *num;
+                self.idx += 1;
+                Ok(num)
+            },
+            _ => self.unexpected(expected),
+        }
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        match self.peek() {
+            Some(Token::Symbol(sym)) => *sym == symbol,
+            _ => false,
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(ident)) => ident == keyword,
+            _ => false,
+        }
+    }
+
+    ///Parses identifier that can be used as variable name.
+    fn variable(&mut self) -> LineResult<String> {
+        let column = self.column();
+        let name = self.ident("variable name")?;
+
+        if RESERVED.contains(&name.as_str()) {
+            Err((column, ErrorKind::ReservedWord(name)))
+        } else {
+            Ok(name)
+        }
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> LineResult<()> {
+        match self.peek() {
+            Some(Token::Symbol(sym)) if *sym == symbol => {
+                self.idx += 1;
+                Ok(())
+            },
+            _ => self.unexpected(symbol),
+        }
+    }
+
+    ///Parses stage position, either name or offset from center.
+    fn stage_position(&mut self) -> LineResult<StagePosition> {
+        match self.peek() {
+            Some(Token::Int(_)) | Some(Token::Float(_)) => return self.number("stage position").map(StagePosition::Offset),
+            Some(Token::Symbol("-")) => {
+                self.idx += 1;
+                return self.number("offset").map(|offset| StagePosition::Offset(-offset));
+            },
+            _ => (),
+        }
+
+        let column = self.column();
+        let name = self.dashed_ident("stage position")?;
+
+        match StagePosition::from_name(&name) {
+            Some(position) => Ok(position),
+            None => Err((column, ErrorKind::UnknownPosition(name))),
+        }
+    }
+
+    ///Parses identifier that consists of several words joined by `-`, like `ease-in-out`.
+    fn dashed_ident(&mut self, expected: &'static str) -> LineResult<String> {
+        let mut name = self.ident(expected)?;
+
+        //Lexer splits such identifiers into separate tokens
+        while self.is_symbol("-") {
+            if let Some(Token::Ident(_)) = self.peek_at(1) {
+                self.idx += 1;
+                name.push('-');
+                name.push_str(&self.ident(expected)?);
+            } else {
+                break;
+            }
+        }
+
+        Ok(name)
+    }
+
+    ///Parses background fit, if any.
+    fn background_fit(&mut self) -> LineResult<BackgroundFit> {
+        match self.peek() {
+            Some(Token::Ident(ident)) if ident != "with" => (),
+            _ => return Ok(BackgroundFit::default()),
+        }
+
+        let column = self.column();
+        let name = self.ident("background fit")?;
+        match BackgroundFit::from_name(&name) {
+            Some(fit) => Ok(fit),
+            None => Err((column, ErrorKind::UnknownFit(name))),
+        }
+    }
+
+    ///Parses name of audio channel.
+    fn channel(&mut self) -> LineResult<Channel> {
+        let column = self.column();
+        let name = self.ident("audio channel")?;
+
+        match Channel::from_name(&name) {
+            Some(channel) => Ok(channel),
+            None => Err((column, ErrorKind::UnknownChannel(name))),
+        }
+    }
+
+    ///Parses `fade DURATION` part of command, if any.
+    fn fade(&mut self) -> LineResult<Option<f32>> {
+        if !self.is_keyword("fade") {
+            return Ok(None);
+        }
+        self.idx += 1;
+
+        self.number("fade duration").map(Some)
+    }
+
+    ///Parses `over DURATION [EASING]` part of command, if any.
+    fn motion(&mut self) -> LineResult<Option<Motion>> {
+        if !self.is_keyword("over") {
+            return Ok(None);
+        }
+        self.idx += 1;
+
+        let duration = self.number("duration")?;
+        let easing = match self.peek() {
+            Some(Token::Ident(_)) => {
+                let column = self.column();
+                let name = self.dashed_ident("easing")?;
+                match Easing::from_name(&name) {
+                    Some(easing) => easing,
+                    None => return Err((column, ErrorKind::UnknownEasing(name))),
+                }
+            },
+            _ => Easing::default(),
+        };
+
+        Ok(Some(Motion {
+            duration,
+            easing,
+        }))
+    }
+
+    ///Parses `with TRANSITION [DURATION]` part of command, if any.
+    ///
+    ///Rule transition is `with rule "MASK" [DURATION] [soft SOFTNESS]`.
+    fn transition(&mut self) -> LineResult<Option<Transition>> {
+        if !self.is_keyword("with") {
+            return Ok(None);
+        }
+        self.idx += 1;
+
+        let column = self.column();
+        let name = self.dashed_ident("transition")?;
+        let mask = if name == "rule" {
+            Some(self.string("mask name")?)
+        } else {
+            None
+        };
+
+        let duration = match self.peek() {
+            Some(Token::Int(_)) | Some(Token::Float(_)) => self.number("duration")?,
+            _ => transition::DEFAULT_DURATION,
+        };
+
+        let kind = match mask {
+            Some(mask) => {
+                let softness = if self.is_keyword("soft") {
+                    self.idx += 1;
+                    self.number("softness")?
+                } else {
+                    transition::DEFAULT_SOFTNESS
+                };
+                transition::Kind::rule(&mask, softness)
+            },
+            None => match transition::Kind::from_name(&name) {
+                Some(kind) => kind,
+                None => return Err((column, ErrorKind::UnknownTransition(name))),
+            },
+        };
+
+        Ok(Some(Transition {
+            kind,
+            duration,
+        }))
+    }
+
+    ///Verifies that there is nothing left on the line.
+    fn finish(&self) -> LineResult<()> {
+        match self.tokens.get(self.idx) {
+            Some(_) => self.unexpected("end of line"),
+            None => Ok(()),
+        }
+    }
+}
+
+///Parses expression.
+///
+///Precedence from lowest to highest: `or`, `and`, `not`, comparison, `+ -`, `* / %`, unary `-`.
+fn expr(line: &mut Line) -> LineResult<Expr> {
+    let mut left = expr_and(line)?;
+
+    while line.is_keyword("or") {
+        line.idx += 1;
+        left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(expr_and(line)?));
+    }
+
+    Ok(left)
+}
+
+fn expr_and(line: &mut Line) -> LineResult<Expr> {
+    let mut left = expr_not(line)?;
+
+    while line.is_keyword("and") {
+        line.idx += 1;
+        left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(expr_not(line)?));
+    }
+
+    Ok(left)
+}
+
+fn expr_not(line: &mut Line) -> LineResult<Expr> {
+    if line.is_keyword("not") {
+        line.idx += 1;
+        Ok(Expr::Unary(UnaryOp::Not, Box::new(expr_not(line)?)))
+    } else {
+        expr_cmp(line)
+    }
+}
+
+fn expr_cmp(line: &mut Line) -> LineResult<Expr> {
+    const OPS: &[(&'static str, BinaryOp)] = &[
+        ("==", BinaryOp::Eq),
+        ("!=", BinaryOp::NotEq),
+        ("<", BinaryOp::Less),
+        ("<=", BinaryOp::LessEq),
+        (">", BinaryOp::Greater),
+        (">=", BinaryOp::GreaterEq),
+    ];
+
+    let left = expr_add(line)?;
+
+    //Comparisons are not chained
+    for (symbol, op) in OPS {
+        if line.is_symbol(symbol) {
+            line.idx += 1;
+            return Ok(Expr::Binary(*op, Box::new(left), Box::new(expr_add(line)?)));
+        }
+    }
+
+    Ok(left)
+}
+
+fn expr_add(line: &mut Line) -> LineResult<Expr> {
+    let mut left = expr_mul(line)?;
+
+    loop {
+        let op = if line.is_symbol("+") {
+            BinaryOp::Add
+        } else if line.is_symbol("-") {
+            BinaryOp::Sub
+        } else {
+            return Ok(left);
+        };
+
+        line.idx += 1;
+        left = Expr::Binary(op, Box::new(left), Box::new(expr_mul(line)?));
+    }
+}
+
+fn expr_mul(line: &mut Line) -> LineResult<Expr> {
+    let mut left = expr_unary(line)?;
+
+    loop {
+        let op = if line.is_symbol("*") {
+            BinaryOp::Mul
+        } else if line.is_symbol("/") {
+            BinaryOp::Div
+        } else if line.is_symbol("%") {
+            BinaryOp::Rem
+        } else {
+            return Ok(left);
+        };
+
+        line.idx += 1;
+        left = Expr::Binary(op, Box::new(left), Box::new(expr_unary(line)?));
+    }
+}
+
+fn expr_unary(line: &mut Line) -> LineResult<Expr> {
+    if line.is_symbol("-") {
+        line.idx += 1;
+        Ok(Expr::Unary(UnaryOp::Neg, Box::new(expr_unary(line)?)))
+    } else {
+        expr_primary(line)
+    }
+}
+
+//Expressions are integer-only, so fractional number is reported as unexpected token
+fn expr_primary(line: &mut Line) -> LineResult<Expr> {
+    const EXPECTED: &'static str = "expression";
+
+    let expr = match line.peek() {
+        Some(Token::Int(num)) => Expr::Value(Value::Int(*num)),
+        Some(Token::Str(text)) => Expr::Value(Value::Str(text.clone())),
+        Some(Token::Ident(ident)) if ident == "true" => Expr::Value(Value::Bool(true)),
+        Some(Token::Ident(ident)) if ident == "false" => Expr::Value(Value::Bool(false)),
+        Some(Token::Ident(_)) => return line.variable().map(Expr::Var),
+        Some(Token::Symbol("(")) => {
+            line.idx += 1;
+            let inner = expr(line)?;
+            line.symbol(")")?;
+            return Ok(inner);
+        },
+        _ => return line.unexpected(EXPECTED),
+    };
+
+    line.idx += 1;
+    Ok(expr)
+}
+
+///Block of statements with jumps to resolve when its end is known.
+struct IfBlock {
+    //Branch that should jump to the next `elif`, `else` or `end`
+    branch: Option<Position>,
+    //Jumps at the end of each branch's body, that should go to `end`
+    exits: Vec<Position>,
+    has_else: bool,
+    line: usize,
+}
+
+///Script parser state.
+struct Parser {
+    statements: Vec<Statement>,
+    labels: HashMap<String, usize>,
+    //Jump targets to verify once whole script is parsed: (label, line, column)
+    jumps: Vec<(String, usize, usize)>,
+    //Choice block that is being parsed: (line, options)
+    choice: Option<(usize, Vec<ChoiceOption>)>,
+    blocks: Vec<IfBlock>,
+}
+
+impl Parser {
+    fn new() -> Self {
+        Self {
+            statements: Vec::new(),
+            labels: HashMap::new(),
+            jumps: Vec::new(),
+            choice: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    ///Points branch or goto at specified statement to the next statement.
+    fn patch(&mut self, idx: Position) {
+        let next = self.statements.len();
+        match self.statements[idx].command {
+            Command::Branch { ref mut target, .. } => *target = next,
+            Command::Goto(ref mut target) => *target = next,
+            _ => unreach!(),
+        }
+    }
+
+    ///Parses `if`, `elif`, `else` and `end`
+    fn parse_block(&mut self, keyword: &str, line_num: usize, column: usize, line: &mut Line) -> LineResult<()> {
+        match keyword {
+            "if" => {
+                let condition = expr(line)?;
+                line.finish()?;
+
+                self.blocks.push(IfBlock {
+                    branch: Some(self.statements.len()),
+                    exits: Vec::new(),
+                    has_else: false,
+                    line: line_num,
+                });
+                self.push(line_num, Command::Branch { condition, target: 0 });
+            },
+            "elif" => {
+                let condition = expr(line)?;
+                line.finish()?;
+
+                let branch = match self.blocks.last_mut() {
+                    Some(block) => if block.has_else {
+                        return Err((column, ErrorKind::UnmatchedBlock("elif")));
+                    } else {
+                        block.branch.take()
+                    },
+                    None => return Err((column, ErrorKind::UnmatchedBlock("elif"))),
+                };
+
+                let exit = self.statements.len();
+                self.push(line_num, Command::Goto(0));
+                if let Some(branch) = branch {
+                    self.patch(branch);
+                }
+
+                let branch = self.statements.len();
+                self.push(line_num, Command::Branch { condition, target: 0 });
+
+                match self.blocks.last_mut() {
+                    Some(block) => {
+                        block.exits.push(exit);
+                        block.branch = Some(branch);
+                    },
+                    None => unreach!(),
+                }
+            },
+            "else" => {
+                line.finish()?;
+
+                let branch = match self.blocks.last_mut() {
+                    Some(block) => if block.has_else {
+                        return Err((column, ErrorKind::UnmatchedBlock("else")));
+                    } else {
+                        block.has_else = true;
+                        block.branch.take()
+                    },
+                    None => return Err((column, ErrorKind::UnmatchedBlock("else"))),
+                };
+
+                let exit = self.statements.len();
+                self.push(line_num, Command::Goto(0));
+                if let Some(branch) = branch {
+                    self.patch(branch);
+                }
+
+                match self.blocks.last_mut() {
+                    Some(block) => block.exits.push(exit),
+                    None => unreach!(),
+                }
+            },
+            "end" => {
+                line.finish()?;
+
+                let block = match self.blocks.pop() {
+                    Some(block) => block,
+                    None => return Err((column, ErrorKind::UnmatchedBlock("end"))),
+                };
+
+                for idx in block.branch.into_iter().chain(block.exits) {
+                    self.patch(idx);
+                }
+            },
+            _ => unreach!(),
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, line: usize, command: Command) {
+        self.statements.push(Statement {
+            line,
+            command,
+        });
+    }
+
+    ///Parses dialogue line after speaker.
+    fn parse_say(&mut self, speaker: Option<String>, line: &mut Line) -> LineResult<Command> {
+        let text = line.string("dialogue text")?;
+        let mut speed = None;
+        let mut voice = None;
+
+        while line.peek().is_some() {
+            let column = line.column();
+            match line.ident("line option")?.as_str() {
+                "speed" => speed = Some(line.number("text speed")?),
+                "voice" => voice = Some(line.string("voice file")?),
+                option => return Err((column, ErrorKind::UnknownOption(option.to_owned()))),
+            }
+        }
+
+        Ok(Command::Say {
+            speaker,
+            text,
+            speed,
+            voice,
+        })
+    }
+
+    ///Parses line within choice block.
+    ///
+    ///Returns `Choice` command when block is over.
+    fn parse_choice_option(&mut self, line_num: usize, line: &mut Line) -> LineResult<Option<Command>> {
+        match line.peek() {
+            Some(Token::Ident(ident)) if ident == "end" => {
+                line.idx += 1;
+                line.finish()?;
+
+                return match self.choice.take() {
+                    Some((_, ref options)) if options.is_empty() => Err((1, ErrorKind::EmptyChoice)),
+                    Some((_, options)) => Ok(Some(Command::Choice(options))),
+                    None => unreach!(),
+                };
+            },
+            _ => (),
+        }
+
+        let text = line.string("choice text")?;
+        line.symbol("->")?;
+        let column = line.column();
+        let target = line.ident("label name")?;
+        self.jumps.push((target.clone(), line_num, column));
+
+        let mut option = ChoiceOption {
+            text,
+            target,
+            condition: None,
+            enabled: None,
+        };
+
+        while line.peek().is_some() {
+            let column = line.column();
+            match line.ident("choice option")?.as_str() {
+                "if" => option.condition = Some(expr(line)?),
+                "enable" => option.enabled = Some(expr(line)?),
+                name => return Err((column, ErrorKind::UnknownOption(name.to_owned()))),
+            }
+        }
+
+        match self.choice.as_mut() {
+            Some((_, options)) => options.push(option),
+            None => unreach!(),
+        }
+
+        Ok(None)
+    }
+
+    fn parse_line(&mut self, line_num: usize, line: &mut Line) -> LineResult<()> {
+        if let Some((start, _)) = self.choice {
+            if let Some(command) = self.parse_choice_option(line_num, line)? {
+                self.push(start, command);
+            }
+
+            return Ok(());
+        }
+
+        let command = match line.peek() {
+            Some(Token::Str(_)) => self.parse_say(None, line)?,
+            Some(Token::Ident(ident)) if !COMMANDS.contains(&ident.as_str()) && line.peek_at(1).map(|token| match token { Token::Str(_) => true, _ => false }).unwrap_or(false) => {
+                let speaker = line.ident("speaker")?;
+                self.parse_say(Some(speaker), line)?
+            },
+            Some(Token::Ident(cmd)) => {
+                let column = line.column();
+                let cmd = cmd.clone();
+                line.idx += 1;
+
+                match cmd.as_str() {
+                    "label" => {
+                        let column = line.column();
+                        let name = line.ident("label name")?;
+                        line.symbol(":")?;
+
+                        if self.labels.contains_key(&name) {
+                            return Err((column, ErrorKind::DuplicateLabel(name)));
+                        }
+                        self.labels.insert(name.clone(), self.statements.len());
+
+                        Command::Label(name)
+                    },
+                    "chapter" => Command::Chapter(line.string("chapter name")?),
+                    "show" => {
+                        let name = line.ident("sprite name")?;
+                        let expression = if line.is_keyword("at") || line.is_keyword("with") {
+                            None
+                        } else {
+                            line.opt_ident()
+                        };
+                        let position = if line.is_keyword("at") {
+                            line.idx += 1;
+                            Some(line.stage_position()?)
+                        } else {
+                            None
+                        };
+
+                        Command::Show {
+                            name,
+                            expression,
+                            position,
+                            transition: line.transition()?,
+                        }
+                    },
+                    "move" => {
+                        let name = line.ident("sprite name")?;
+                        if !line.is_keyword("to") {
+                            return line.unexpected("'to'");
+                        }
+                        line.idx += 1;
+
+                        Command::Move {
+                            name,
+                            position: line.stage_position()?,
+                            motion: line.motion()?,
+                        }
+                    },
+                    "wait" => Command::Wait,
+                    "hide" => Command::Hide {
+                        name: line.ident("sprite name")?,
+                        transition: line.transition()?,
+                    },
+                    "bg" => {
+                        let name = line.ident("background name")?;
+                        let (name, fit) = match name.as_str() {
+                            "clear" => (None, BackgroundFit::default()),
+                            _ => (Some(name), line.background_fit()?),
+                        };
+
+                        Command::Background {
+                            name,
+                            fit,
+                            transition: line.transition()?,
+                        }
+                    },
+                    "play" => {
+                        let channel = line.channel()?;
+                        let file = line.string("file name")?;
+                        let looping = if line.is_keyword("loop") {
+                            line.idx += 1;
+                            true
+                        } else if line.is_keyword("once") {
+                            line.idx += 1;
+                            false
+                        } else {
+                            channel.is_looped()
+                        };
+
+                        Command::Play {
+                            channel,
+                            file,
+                            looping,
+                            fade: line.fade()?,
+                        }
+                    },
+                    "stop" => Command::Stop {
+                        channel: line.channel()?,
+                        fade: line.fade()?,
+                    },
+                    "volume" => Command::Volume {
+                        channel: line.channel()?,
+                        volume: line.number("volume")?,
+                    },
+                    "jump" => {
+                        let column = line.column();
+                        let label = line.ident("label name")?;
+                        self.jumps.push((label.clone(), line_num, column));
+
+                        Command::Jump(label)
+                    },
+                    "choice" => {
+                        line.finish()?;
+                        self.choice = Some((line_num, Vec::new()));
+                        return Ok(());
+                    },
+                    "if" | "elif" | "else" | "end" => return self.parse_block(&cmd, line_num, column, line),
+                    "flag" => Command::Set {
+                        name: line.variable()?,
+                        value: Expr::Value(Value::Bool(true)),
+                    },
+                    "set" => {
+                        let name = line.variable()?;
+                        line.symbol("=")?;
+
+                        Command::Set {
+                            name,
+                            value: expr(line)?,
+                        }
+                    },
+                    "inc" | "dec" => {
+                        let name = line.variable()?;
+                        let mut by = Expr::Value(Value::Int(1));
+                        if line.is_keyword("by") {
+                            line.idx += 1;
+                            by = expr(line)?;
+                        }
+
+                        if cmd == "dec" {
+                            by = Expr::Unary(UnaryOp::Neg, Box::new(by));
+                        }
+
+                        Command::Increment {
+                            name,
+                            by,
+                        }
+                    },
+                    _ => return Err((column, ErrorKind::UnknownCommand(cmd))),
+                }
+            },
+            _ => return line.unexpected("command"),
+        };
+
+        line.finish()?;
+        self.push(line_num, command);
+
+        Ok(())
+    }
+}
+
+///Parses script's source.
+///
+///`name` is used to identify script in errors.
+pub fn parse(name: &str, source: &str) -> Result<Script, ParseError> {
+    let mut parser = Parser::new();
+
+    let error = |line, (column, kind)| ParseError {
+        file: name.to_owned(),
+        line,
+        column,
+        kind,
+    };
+
+    for (idx, text) in source.lines().enumerate() {
+        let line_num = idx + 1;
+        let tokens = lexer::tokenize(text).map_err(|error_info| error(line_num, error_info))?;
+
+        if tokens.is_empty() {
+            continue;
+        }
+
+        let mut line = Line {
+            tokens,
+            idx: 0,
+            end: text.chars().count() + 1,
+        };
+
+        parser.parse_line(line_num, &mut line).map_err(|error_info| error(line_num, error_info))?;
+    }
+
+    if let Some((line, _)) = parser.choice {
+        return Err(error(line, (1, ErrorKind::UnclosedChoice)));
+    }
+
+    if let Some(block) = parser.blocks.last() {
+        return Err(error(block.line, (1, ErrorKind::UnclosedBlock)));
+    }
+
+    for (label, line, column) in parser.jumps.drain(..) {
+        if !parser.labels.contains_key(&label) {
+            return Err(error(line, (column, ErrorKind::UndefinedLabel(label))));
+        }
+    }
+
+    Ok(Script {
+        name: name.to_owned(),
+        statements: parser.statements,
+        labels: parser.labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dialogue_and_labels() {
+        let script = parse("test", "# Comment\nlabel start:\nkaoru \"Hello\" speed 10\n\n\"Narration\" voice \"n_001.ogg\"\njump start\n").expect("To parse script");
+
+        assert_eq!(script.label("start"), Some(0));
+        assert_eq!(script.statements.len(), 4);
+        assert_eq!(script.statements[1].line, 3);
+        assert_eq!(script.statements[1].command, Command::Say {
+            speaker: Some("kaoru".to_owned()),
+            text: "Hello".to_owned(),
+            speed: Some(10.0),
+            voice: None,
+        });
+        assert_eq!(script.statements[2].command, Command::Say {
+            speaker: None,
+            text: "Narration".to_owned(),
+            speed: None,
+            voice: Some("n_001.ogg".to_owned()),
+        });
+        assert_eq!(script.statements[3].command, Command::Jump("start".to_owned()));
+    }
+
+    #[test]
+    fn parse_stage_commands() {
+        let script = parse("test", "show kaoru smile at left with dissolve 0.5\nmove kaoru to -300 over 0.5 ease-out\nbg room contain\nplay sfx \"door.wav\" fade 1.0").expect("To parse script");
+
+        assert_eq!(script.statements[0].command, Command::Show {
+            name: "kaoru".to_owned(),
+            expression: Some("smile".to_owned()),
+            position: Some(StagePosition::Left),
+            transition: Some(Transition {
+                kind: transition::Kind::Dissolve,
+                duration: 0.5,
+            }),
+        });
+        assert_eq!(script.statements[1].command, Command::Move {
+            name: "kaoru".to_owned(),
+            position: StagePosition::Offset(-300.0),
+            motion: Some(Motion {
+                duration: 0.5,
+                easing: Easing::EaseOut,
+            }),
+        });
+        assert_eq!(script.statements[2].command, Command::Background {
+            name: Some("room".to_owned()),
+            fit: BackgroundFit::Contain,
+            transition: None,
+        });
+        assert_eq!(script.statements[3].command, Command::Play {
+            channel: Channel::Sfx,
+            file: "door.wav".to_owned(),
+            looping: false,
+            fade: Some(1.0),
+        });
+    }
+
+    #[test]
+    fn parse_branch_targets() {
+        let script = parse("test", "if a\n\"A\"\nelif b\n\"B\"\nelse\n\"C\"\nend\n\"D\"").expect("To parse script");
+        let var = |name: &str| Expr::Var(name.to_owned());
+
+        assert_eq!(script.statements.len(), 8);
+        //False condition goes to the next branch
+        assert_eq!(script.statements[0].command, Command::Branch { condition: var("a"), target: 3 });
+        assert_eq!(script.statements[3].command, Command::Branch { condition: var("b"), target: 6 });
+        //End of each branch goes past `end`
+        assert_eq!(script.statements[2].command, Command::Goto(7));
+        assert_eq!(script.statements[5].command, Command::Goto(7));
+        assert_eq!(script.statements[2].line, 3);
+
+        let script = parse("test", "if a\nif b\n\"X\"\nend\nend\n\"Y\"").expect("To parse script");
+        assert_eq!(script.statements[0].command, Command::Branch { condition: var("a"), target: 3 });
+        assert_eq!(script.statements[1].command, Command::Branch { condition: var("b"), target: 3 });
+    }
+
+    #[test]
+    fn parse_block_errors() {
+        let error = parse("test", "if a\n\"A\"").unwrap_err();
+        assert_eq!((error.line, error.kind), (1, ErrorKind::UnclosedBlock));
+
+        let error = parse("test", "if a\nelse\nelif b\nend").unwrap_err();
+        assert_eq!((error.line, error.kind), (3, ErrorKind::UnmatchedBlock("elif")));
+
+        let error = parse("test", "end").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnmatchedBlock("end"));
+
+        let error = parse("test", "set and = 1").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ReservedWord("and".to_owned()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("test", "\"Line\"\njump nowhere").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 6));
+        assert_eq!(error.kind, ErrorKind::UndefinedLabel("nowhere".to_owned()));
+
+        let error = parse("test", "label start:\nlabel start:").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.kind, ErrorKind::DuplicateLabel("start".to_owned()));
+
+        let error = parse("test", "dance kaoru").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnknownCommand("dance".to_owned()));
+
+        let error = parse("test", "\"Unterminated").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnterminatedString);
+
+        let error = parse("test", "set ratio = 0.5").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnexpectedToken { expected: "expression", found: "number 0.5".to_owned() });
+    }
+}
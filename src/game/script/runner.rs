@@ -0,0 +1,432 @@
+use std::sync::Arc;
+use std::fmt;
+
+use super::{Script, Command, Position, LineId};
+use super::expr::{Expr, EvalError};
+use super::variables::{Variables, Value};
+
+///Dialogue line that is currently displayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub speaker: Option<String>,
+    pub text: String,
+    pub speed: Option<f32>,
+    ///Voice clip in `assets/audio`.
+    pub voice: Option<String>,
+    pub id: LineId,
+}
+
+impl Line {
+    ///Returns text with speaker's name.
+    pub fn display(&self) -> String {
+        match self.speaker.as_ref() {
+            Some(speaker) => format!("{}: {}", speaker, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+///Choice option that is offered to player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub text: String,
+    pub target: String,
+    pub enabled: bool,
+}
+
+///Error during script execution.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub file: String,
+    pub line: usize,
+    pub kind: EvalError,
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}:{}: {}", self.file, self.line, self.kind)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+///Executes script statement by statement.
+///
+///Stored as resource, it is advanced by `Game` state on player's input.
+#[derive(Default)]
+pub struct Runner {
+    script: Arc<Script>,
+    position: Position,
+    //Position of statement that produced current line or choice
+    current: Position,
+    //Line in script file of last executed statement
+    line_num: usize,
+    line: Option<Line>,
+    choices: Option<Vec<Choice>>,
+    chapter: Option<String>,
+    //Waiting for animations to finish
+    waiting: bool,
+    finished: bool,
+}
+
+impl Runner {
+    pub fn new(script: Script) -> Self {
+        Self {
+            script: Arc::new(script),
+            position: 0,
+            current: 0,
+            line_num: 0,
+            line: None,
+            choices: None,
+            chapter: None,
+            waiting: false,
+            finished: false,
+        }
+    }
+
+    ///Creates runner that continues from position of saved line.
+    pub fn restore(script: Script, position: Position, chapter: Option<String>) -> Self {
+        let mut runner = Self::new(script);
+        runner.position = position;
+        runner.chapter = chapter;
+        runner
+    }
+
+    ///Returns name of executed script.
+    pub fn script_name(&self) -> &str {
+        &self.script.name
+    }
+
+    ///Returns executed script.
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    ///Returns current position in script.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    ///Returns position of currently displayed line or choice.
+    ///
+    ///Runner restored at this position is going to show them again.
+    pub fn current(&self) -> Position {
+        self.current
+    }
+
+    ///Returns currently displayed line.
+    pub fn line(&self) -> Option<&Line> {
+        self.line.as_ref()
+    }
+
+    ///Returns choice that player needs to make before script can continue.
+    pub fn choices(&self) -> Option<&[Choice]> {
+        self.choices.as_ref().map(|choices| choices.as_slice())
+    }
+
+    ///Returns name of current chapter.
+    pub fn chapter(&self) -> Option<&str> {
+        self.chapter.as_ref().map(String::as_str)
+    }
+
+    ///Returns whether script is paused by `wait` command.
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    ///Continues script after animations are finished.
+    pub fn resume(&mut self) {
+        self.waiting = false;
+    }
+
+    ///Returns whether script is over.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn error(&self, kind: EvalError) -> RuntimeError {
+        RuntimeError {
+            file: self.script.name.clone(),
+            line: self.line_num,
+            kind,
+        }
+    }
+
+    fn check(&self, condition: &Option<Expr>, vars: &Variables) -> Result<bool, RuntimeError> {
+        match condition {
+            Some(condition) => condition.eval_bool(vars).map_err(|error| self.error(error)),
+            None => Ok(true),
+        }
+    }
+
+    ///Returns next command to execute, or `None` when script is over.
+    ///
+    ///Jumps are resolved by runner itself.
+    pub fn step(&mut self) -> Option<Command> {
+        let script = self.script.clone();
+
+        loop {
+            let statement = script.statements.get(self.position)?;
+            self.position += 1;
+            self.line_num = statement.line;
+
+            match &statement.command {
+                Command::Jump(label) => self.jump(label),
+                Command::Goto(target) => self.position = *target,
+                command => return Some(command.clone()),
+            }
+        }
+    }
+
+    fn jump(&mut self, label: &str) {
+        self.position = match self.script.label(label) {
+            Some(position) => position,
+            //Parser verifies that all labels exist
+            None => unreach!(),
+        }
+    }
+
+    ///Moves to the next dialogue line.
+    ///
+    ///Returns stage commands that are met on the way and should be applied
+    ///before displaying new line.
+    ///
+    ///Does nothing while choice is pending.
+    ///On error script is considered finished.
+    pub fn advance(&mut self, vars: &mut Variables) -> Result<Vec<Command>, RuntimeError> {
+        let result = self.execute(vars);
+
+        if result.is_err() {
+            self.line = None;
+            self.finished = true;
+        }
+
+        result
+    }
+
+    fn execute(&mut self, vars: &mut Variables) -> Result<Vec<Command>, RuntimeError> {
+        let mut commands = Vec::new();
+
+        if self.choices.is_some() || self.waiting {
+            return Ok(commands);
+        }
+
+        while let Some(command) = self.step() {
+            match command {
+                Command::Say { speaker, text, speed, voice } => {
+                    self.current = self.position - 1;
+                    self.line = Some(Line {
+                        id: super::line_id(&self.script.name, speaker.as_ref().map(String::as_str), &text),
+                        speaker,
+                        text,
+                        speed,
+                        voice,
+                    });
+                    return Ok(commands);
+                },
+                Command::Choice(options) => {
+                    let mut choices = Vec::with_capacity(options.len());
+
+                    for option in options {
+                        if !self.check(&option.condition, vars)? {
+                            continue;
+                        }
+
+                        choices.push(Choice {
+                            enabled: self.check(&option.enabled, vars)?,
+                            text: option.text,
+                            target: option.target,
+                        });
+                    }
+
+                    if choices.is_empty() {
+                        warn!("{}:{}: all choice options are hidden, skipping", self.script.name, self.line_num);
+                        continue;
+                    }
+
+                    self.current = self.position - 1;
+                    self.choices = Some(choices);
+                    return Ok(commands);
+                },
+                Command::Set { name, value } => {
+                    let value = value.eval(vars).map_err(|error| self.error(error))?;
+                    vars.set(name, value);
+                },
+                Command::Increment { name, by } => {
+                    let by = by.eval(vars).map_err(|error| self.error(error))?;
+                    let value = match (vars.get(&name), by) {
+                        (Some(Value::Int(current)), Value::Int(by)) => current.checked_add(by).map(Value::Int).ok_or(EvalError::Overflow),
+                        (Some(current), by) => Err(EvalError::InvalidOperands {
+                            op: "inc",
+                            left: current.type_name(),
+                            right: by.type_name(),
+                        }),
+                        //Counter that wasn't set yet starts from zero
+                        (None, Value::Int(by)) => Ok(Value::Int(by)),
+                        (None, by) => Err(EvalError::InvalidOperands {
+                            op: "inc",
+                            left: "integer",
+                            right: by.type_name(),
+                        }),
+                    };
+                    let value = value.map_err(|error| self.error(error))?;
+                    vars.set(name, value);
+                },
+                Command::Branch { condition, target } => {
+                    if !condition.eval_bool(vars).map_err(|error| self.error(error))? {
+                        self.position = target;
+                    }
+                },
+                Command::Chapter(name) => self.chapter = Some(name),
+                //Current line stays on screen while waiting
+                Command::Wait => {
+                    self.waiting = true;
+                    return Ok(commands);
+                },
+                Command::Label(_) => (),
+                command => commands.push(command),
+            }
+        }
+
+        self.line = None;
+        self.finished = true;
+        Ok(commands)
+    }
+
+    ///Picks choice option by its index.
+    ///
+    ///Returns `false` if there is no such option or it is disabled.
+    pub fn choose(&mut self, idx: usize) -> bool {
+        let target = match self.choices.as_ref().and_then(|choices| choices.get(idx)) {
+            Some(choice) if choice.enabled => choice.target.clone(),
+            _ => return false,
+        };
+
+        self.choices = None;
+        self.jump(&target);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::script::parse;
+
+    fn runner(source: &str) -> Runner {
+        Runner::new(parse("test", source).expect("To parse script"))
+    }
+
+    fn text(runner: &Runner) -> Option<&str> {
+        runner.line().map(|line| line.text.as_str())
+    }
+
+    #[test]
+    fn advance_lines() {
+        let mut vars = Variables::default();
+        let mut runner = runner("chapter \"One\"\nkaoru \"First\"\nbg room\n\"Second\"");
+
+        assert!(runner.advance(&mut vars).expect("To advance").is_empty());
+        assert_eq!(runner.line().and_then(|line| line.speaker.as_ref()).map(String::as_str), Some("kaoru"));
+        assert_eq!(text(&runner), Some("First"));
+        assert_eq!(runner.chapter(), Some("One"));
+        assert_eq!(runner.current(), 1);
+
+        let commands = runner.advance(&mut vars).expect("To advance");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(text(&runner), Some("Second"));
+        assert!(!runner.is_finished());
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), None);
+        assert!(runner.is_finished());
+    }
+
+    #[test]
+    fn jump_to_label() {
+        let mut vars = Variables::default();
+        let mut runner = runner("jump end\n\"Skipped\"\nlabel end:\n\"Last\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), Some("Last"));
+    }
+
+    #[test]
+    fn choose_option() {
+        let mut vars = Variables::default();
+        let mut runner = runner("choice\n\"Left\" -> left\n\"Right\" -> right enable false\n\"Hidden\" -> left if false\nend\nlabel left:\n\"Went left\"\nlabel right:\n\"Went right\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        let choices = runner.choices().expect("To offer choice").to_vec();
+        assert_eq!(choices.len(), 2);
+        assert_eq!(choices[0].text, "Left");
+        assert!(choices[0].enabled);
+        assert!(!choices[1].enabled);
+
+        //Script doesn't continue until option is chosen
+        runner.advance(&mut vars).expect("To advance");
+        assert!(runner.choices().is_some());
+
+        assert!(!runner.choose(1));
+        assert!(!runner.choose(2));
+        assert!(runner.choose(0));
+        assert!(runner.choices().is_none());
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), Some("Went left"));
+    }
+
+    #[test]
+    fn branch_on_variables() {
+        let mut vars = Variables::default();
+        let mut runner = runner("flag met\nset affection = 1\ninc affection by 2\ndec affection\nif affection >= 2 and met\n\"Yes\"\nelse\n\"No\"\nend\n\"After\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(vars.get("affection"), Some(&Value::Int(2)));
+        assert_eq!(text(&runner), Some("Yes"));
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), Some("After"));
+    }
+
+    #[test]
+    fn increment_undefined() {
+        let mut vars = Variables::default();
+        let mut runner = runner("inc affection by 2\ndec courage\n\"Line\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(vars.get("affection"), Some(&Value::Int(2)));
+        assert_eq!(vars.get("courage"), Some(&Value::Int(-1)));
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let mut vars = Variables::default();
+        let mut runner = runner("\"Before\"\nset name = \"Kaoru\"\ninc name\n\"After\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        let error = runner.advance(&mut vars).unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.kind, EvalError::InvalidOperands { op: "inc", left: "string", right: "integer" });
+        assert!(runner.is_finished());
+        assert_eq!(text(&runner), None);
+    }
+
+    #[test]
+    fn wait_for_animations() {
+        let mut vars = Variables::default();
+        let mut runner = runner("\"Before\"\nwait\n\"After\"");
+
+        runner.advance(&mut vars).expect("To advance");
+        runner.advance(&mut vars).expect("To advance");
+        assert!(runner.is_waiting());
+        assert_eq!(text(&runner), Some("Before"));
+
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), Some("Before"));
+
+        runner.resume();
+        runner.advance(&mut vars).expect("To advance");
+        assert_eq!(text(&runner), Some("After"));
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Serialize, Deserialize};
+
+use std::collections::HashMap;
+use std::fmt;
+
+///Value of story variable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+impl Value {
+    ///Returns name of value's type for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "integer",
+            Value::Str(_) => "string",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(fmt, "{}", value),
+            Value::Int(value) => write!(fmt, "{}", value),
+            Value::Str(value) => write!(fmt, "\"{}\"", value),
+        }
+    }
+}
+
+///Story variables.
+///
+///Stored as resource, next to `ui::Resources`.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Variables {
+    inner: HashMap<String, Value>,
+}
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.inner.get(name)
+    }
+
+    pub fn set<S: Into<String>>(&mut self, name: S, value: Value) {
+        self.inner.insert(name.into(), value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(&String, &Value)> {
+        self.inner.iter()
+    }
+}
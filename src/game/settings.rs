@@ -0,0 +1,550 @@
+//!User settings
+//!
+//!Settings are stored as RON in user's config directory, missing fields take default values.
+
+use serde::{Serialize, Deserialize};
+
+use std::path::PathBuf;
+use std::{fmt, fs, io};
+
+use crate::game::audio::{self, Channel};
+use crate::game::components::typewriter;
+use crate::game::save;
+
+const SETTINGS_FILE: &'static str = "settings.ron";
+///Resolutions offered by settings screen.
+pub const RESOLUTIONS: &[(u32, u32)] = &[(800, 600), (1024, 768), (1280, 720), (1366, 768), (1600, 900), (1920, 1080)];
+const MIN_TEXT_SPEED: f32 = 5.0;
+const MAX_TEXT_SPEED: f32 = 200.0;
+const MAX_AUTO_DELAY: f32 = 0.2;
+///Precision of auto delay, in seconds.
+const AUTO_DELAY_PRECISION: f32 = 0.001;
+const VOLUME_PRECISION: f32 = 0.01;
+///Part of slider that is moved by single step.
+const LEVEL_STEP: f32 = 0.01;
+
+///Returns directory with user configuration.
+pub fn config_dir() -> PathBuf {
+    match dirs::config_dir() {
+        Some(dir) => dir.join(save::APP_DIR),
+        None => PathBuf::from(save::APP_DIR),
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Serialize(ron::ser::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            Error::Serialize(error) => write!(fmt, "unable to serialize settings: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+///Volume of audio channels, from 0 to 1.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Volumes {
+    pub bgm: f32,
+    pub sfx: f32,
+    pub voice: f32,
+    pub ambient: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self {
+            bgm: audio::DEFAULT_VOLUME,
+            sfx: audio::DEFAULT_VOLUME,
+            voice: audio::DEFAULT_VOLUME,
+            ambient: audio::DEFAULT_VOLUME,
+        }
+    }
+}
+
+impl Volumes {
+    pub fn get(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Bgm => self.bgm,
+            Channel::Sfx => self.sfx,
+            Channel::Voice => self.voice,
+            Channel::Ambient => self.ambient,
+        }
+    }
+
+    fn get_mut(&mut self, channel: Channel) -> &mut f32 {
+        match channel {
+            Channel::Bgm => &mut self.bgm,
+            Channel::Sfx => &mut self.sfx,
+            Channel::Voice => &mut self.voice,
+            Channel::Ambient => &mut self.ambient,
+        }
+    }
+}
+
+///How game window is shown.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum WindowMode {
+    Windowed,
+    ///Window without decorations that covers whole monitor.
+    Borderless,
+    ///Fullscreen with exclusive access to monitor.
+    Exclusive,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [WindowMode::Windowed, WindowMode::Borderless, WindowMode::Exclusive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Exclusive => "Fullscreen",
+        }
+    }
+}
+
+///How setting is changed on settings screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    ///Steps through list of values.
+    Choice,
+    ///Switches between on and off.
+    Toggle,
+    ///Picks value within range.
+    Slider,
+}
+
+///Setting that can be changed on settings screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Item {
+    Resolution,
+    WindowMode,
+    Vsync,
+    TextSpeed,
+    AutoDelay,
+    Volume(Channel),
+    SkipUnread,
+    StopVoice,
+}
+
+impl Item {
+    pub const ALL: [Item; 11] = [
+        Item::Resolution,
+        Item::WindowMode,
+        Item::Vsync,
+        Item::TextSpeed,
+        Item::AutoDelay,
+        Item::Volume(Channel::Bgm),
+        Item::Volume(Channel::Sfx),
+        Item::Volume(Channel::Voice),
+        Item::Volume(Channel::Ambient),
+        Item::SkipUnread,
+        Item::StopVoice,
+    ];
+
+    ///Returns whether setting changes window.
+    pub fn is_window(self) -> bool {
+        match self {
+            Item::Resolution | Item::WindowMode => true,
+            _ => false,
+        }
+    }
+
+    pub fn control(self) -> Control {
+        match self {
+            Item::Resolution | Item::WindowMode => Control::Choice,
+            Item::Vsync | Item::SkipUnread | Item::StopVoice => Control::Toggle,
+            Item::TextSpeed | Item::AutoDelay | Item::Volume(_) => Control::Slider,
+        }
+    }
+
+    ///Returns name of setting that is shown next to its value.
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Resolution => "Resolution",
+            Item::WindowMode => "Window",
+            Item::Vsync => "VSync (after restart)",
+            Item::TextSpeed => "Text speed",
+            Item::AutoDelay => "Auto delay per character",
+            Item::Volume(Channel::Bgm) => "Music volume",
+            Item::Volume(Channel::Sfx) => "Sound volume",
+            Item::Volume(Channel::Voice) => "Voice volume",
+            Item::Volume(Channel::Ambient) => "Ambient volume",
+            Item::SkipUnread => "Skip unread text",
+            Item::StopVoice => "Stop voice on advance",
+        }
+    }
+}
+
+#[inline]
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+#[inline]
+fn round_to(value: f32, precision: f32) -> f32 {
+    (value / precision).round() * precision
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    ///Size of window when it is not fullscreen.
+    pub resolution: (u32, u32),
+    ///Position of window when it is not fullscreen.
+    pub window_position: Option<(i32, i32)>,
+    pub window_mode: WindowMode,
+    ///Fullscreen mode that is toggled by Alt+Enter or F11.
+    pub fullscreen_mode: WindowMode,
+    ///Takes effect after restart.
+    pub vsync: bool,
+    ///Characters per second, zero shows text at once.
+    pub text_speed: f32,
+    ///Delay of auto mode per character, in seconds.
+    pub auto_delay: f32,
+    pub volumes: Volumes,
+    ///Whether skip mode continues through unseen lines.
+    pub skip_unread: bool,
+    ///Whether voice is stopped when dialogue advances.
+    pub stop_voice: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            resolution: (1280, 720),
+            window_position: None,
+            window_mode: WindowMode::Windowed,
+            fullscreen_mode: WindowMode::Borderless,
+            vsync: true,
+            text_speed: typewriter::DEFAULT_SPEED,
+            auto_delay: typewriter::DEFAULT_AUTO_DELAY,
+            volumes: Volumes::default(),
+            skip_unread: false,
+            stop_voice: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        config_dir().join(SETTINGS_FILE)
+    }
+
+    ///Loads settings, using defaults if file cannot be read.
+    pub fn load() -> Self {
+        let path = Self::path();
+
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("{}", Error::Io(path, error));
+                return Self::default();
+            }
+        };
+
+        match ron::de::from_str(&data) {
+            Ok(settings) => settings,
+            Err(error) => {
+                warn!("{}: invalid settings: {}", path.display(), error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn store(&self) -> Result<(), Error> {
+        let path = Self::path();
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(Error::Serialize)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| Error::Io(dir.to_path_buf(), error))?;
+        }
+
+        fs::write(&path, data).map_err(|error| Error::Io(path.clone(), error))
+    }
+
+    ///Returns audio that plays on `output` with volumes of settings.
+    pub fn audio(&self, output: Option<amethyst::audio::output::Output>) -> audio::Audio {
+        let mut audio = audio::Audio::new(output);
+        for channel in Channel::ALL.iter() {
+            audio.set_volume(*channel, self.volumes.get(*channel));
+        }
+        audio
+    }
+
+    ///Applies settings that can be changed while game is running, except window.
+    pub fn apply(&self, world: &mut amethyst::prelude::World) {
+        world.write_resource::<typewriter::TextSpeed>().0 = self.text_speed;
+        world.write_resource::<typewriter::AutoDelay>().0 = self.auto_delay;
+        world.write_resource::<save::SkipUnread>().0 = self.skip_unread;
+        world.write_resource::<audio::StopVoice>().0 = self.stop_voice;
+
+        let mut audio = world.write_resource::<audio::Audio>();
+        for channel in Channel::ALL.iter() {
+            audio.set_volume(*channel, self.volumes.get(*channel));
+        }
+    }
+
+    ///Applies resolution and window mode to window.
+    pub fn apply_window(&self, world: &mut amethyst::prelude::World) {
+        self.send_window(&mut world.write_resource::<amethyst::renderer::WindowMessages>());
+    }
+
+    ///Sends command to apply resolution and window mode.
+    ///
+    ///Window is changed by renderer at the end of frame.
+    pub fn send_window(&self, messages: &mut amethyst::renderer::WindowMessages) {
+        let (width, height) = self.resolution;
+        let position = self.window_position;
+        let mode = self.window_mode;
+
+        messages.send_command(move |window| {
+            match mode {
+                WindowMode::Windowed => {
+                    window.set_fullscreen(None);
+                    window.set_decorations(true);
+                    window.set_inner_size((width, height).into());
+                    if let Some((x, y)) = position {
+                        window.set_position((x, y).into());
+                    }
+                },
+                WindowMode::Borderless => {
+                    let monitor = window.get_current_monitor();
+                    let hidpi = monitor.get_hidpi_factor();
+
+                    window.set_fullscreen(None);
+                    window.set_decorations(false);
+                    window.set_position(monitor.get_position().to_logical(hidpi));
+                    window.set_inner_size(monitor.get_dimensions().to_logical(hidpi));
+                },
+                WindowMode::Exclusive => {
+                    window.set_decorations(true);
+                    window.set_fullscreen(Some(window.get_current_monitor()));
+                },
+            }
+        });
+    }
+
+    ///Switches between windowed and last used fullscreen mode.
+    pub fn toggle_fullscreen(&mut self) {
+        self.window_mode = match self.window_mode {
+            WindowMode::Windowed => self.fullscreen_mode,
+            WindowMode::Borderless | WindowMode::Exclusive => WindowMode::Windowed,
+        };
+    }
+
+    ///Returns text that describes value of setting.
+    pub fn value(&self, item: Item) -> String {
+        match item {
+            Item::Resolution => format!("{}x{}", self.resolution.0, self.resolution.1),
+            Item::WindowMode => self.window_mode.name().to_owned(),
+            Item::Vsync => on_off(self.vsync).to_owned(),
+            Item::TextSpeed => if self.text_speed > 0.0 {
+                format!("{} chars/s", self.text_speed)
+            } else {
+                "Instant".to_owned()
+            },
+            Item::AutoDelay => format!("{:.3}s", self.auto_delay),
+            Item::Volume(channel) => format!("{}%", (self.volumes.get(channel) * 100.0).round()),
+            Item::SkipUnread => on_off(self.skip_unread).to_owned(),
+            Item::StopVoice => on_off(self.stop_voice).to_owned(),
+        }
+    }
+
+    ///Returns position of slider, from 0 to 1.
+    ///
+    ///Text speed slider shows text at once in its rightmost position.
+    pub fn level(&self, item: Item) -> Option<f32> {
+        match item {
+            Item::TextSpeed => Some(if self.text_speed > 0.0 {
+                ((self.text_speed - MIN_TEXT_SPEED) / (MAX_TEXT_SPEED - MIN_TEXT_SPEED)).max(0.0).min(1.0)
+            } else {
+                1.0
+            }),
+            Item::AutoDelay => Some((self.auto_delay / MAX_AUTO_DELAY).max(0.0).min(1.0)),
+            Item::Volume(channel) => Some(self.volumes.get(channel)),
+            _ => None,
+        }
+    }
+
+    ///Sets value of slider by its position, from 0 to 1.
+    ///
+    ///Value is rounded to precision that is shown, settings other than sliders are left intact.
+    pub fn set_level(&mut self, item: Item, level: f32) {
+        let level = level.max(0.0).min(1.0);
+
+        match item {
+            Item::TextSpeed => self.text_speed = if level < 1.0 {
+                (MIN_TEXT_SPEED + level * (MAX_TEXT_SPEED - MIN_TEXT_SPEED)).round()
+            } else {
+                0.0
+            },
+            Item::AutoDelay => self.auto_delay = round_to(level * MAX_AUTO_DELAY, AUTO_DELAY_PRECISION),
+            Item::Volume(channel) => *self.volumes.get_mut(channel) = round_to(level, VOLUME_PRECISION),
+            _ => (),
+        }
+    }
+
+    ///Changes setting to the next or previous value.
+    ///
+    ///Toggles are flipped in either direction, while sliders move by small step.
+    pub fn change(&mut self, item: Item, forward: bool) {
+        match item {
+            Item::Resolution => {
+                //Resolutions are listed from smallest to largest.
+                //Custom one from settings file steps to the nearest listed in that direction.
+                let area = |(width, height): (u32, u32)| u64::from(width) * u64::from(height);
+                let current = area(self.resolution);
+                let next = if forward {
+                    RESOLUTIONS.iter().find(|resolution| area(**resolution) > current).unwrap_or(&RESOLUTIONS[0])
+                } else {
+                    RESOLUTIONS.iter().rev().find(|resolution| area(**resolution) < current).unwrap_or(&RESOLUTIONS[RESOLUTIONS.len() - 1])
+                };
+                self.resolution = *next;
+            },
+            Item::WindowMode => {
+                let len = WindowMode::ALL.len();
+                let idx = WindowMode::ALL.iter().position(|mode| *mode == self.window_mode).unwrap_or(0);
+                let idx = if forward {
+                    (idx + 1) % len
+                } else {
+                    (idx + len - 1) % len
+                };
+
+                self.window_mode = WindowMode::ALL[idx];
+                if self.window_mode != WindowMode::Windowed {
+                    self.fullscreen_mode = self.window_mode;
+                }
+            },
+            Item::Vsync => self.vsync = !self.vsync,
+            Item::TextSpeed | Item::AutoDelay | Item::Volume(_) => {
+                let level = self.level(item).unwrap_or(0.0);
+                self.set_level(item, if forward { level + LEVEL_STEP } else { level - LEVEL_STEP });
+            },
+            Item::SkipUnread => self.skip_unread = !self.skip_unread,
+            Item::StopVoice => self.stop_voice = !self.stop_voice,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_resolution() {
+        let mut settings = Settings::default();
+
+        settings.resolution = (1920, 1080);
+        settings.change(Item::Resolution, true);
+        assert_eq!(settings.resolution, (800, 600));
+        settings.change(Item::Resolution, false);
+        assert_eq!(settings.resolution, (1920, 1080));
+
+        //Resolution that isn't listed goes to the nearest one
+        settings.resolution = (1280, 1024);
+        settings.change(Item::Resolution, true);
+        assert_eq!(settings.resolution, (1600, 900));
+
+        settings.resolution = (1280, 1024);
+        settings.change(Item::Resolution, false);
+        assert_eq!(settings.resolution, (1366, 768));
+    }
+
+    #[test]
+    fn control_items() {
+        assert_eq!(Item::Resolution.control(), Control::Choice);
+        assert_eq!(Item::WindowMode.control(), Control::Choice);
+        assert_eq!(Item::Volume(Channel::Voice).control(), Control::Slider);
+        assert_eq!(Item::StopVoice.control(), Control::Toggle);
+
+        let settings = Settings::default();
+        for item in Item::ALL.iter() {
+            assert_eq!(settings.level(*item).is_some(), item.control() == Control::Slider, "{:?}", item);
+        }
+    }
+
+    #[test]
+    fn flip_toggle() {
+        let mut settings = Settings::default();
+
+        settings.skip_unread = false;
+        settings.change(Item::SkipUnread, true);
+        assert!(settings.skip_unread);
+        assert_eq!(settings.value(Item::SkipUnread), "On");
+        settings.change(Item::SkipUnread, false);
+        assert!(!settings.skip_unread);
+        assert_eq!(settings.value(Item::SkipUnread), "Off");
+    }
+
+    #[test]
+    fn set_volume_level() {
+        let mut settings = Settings::default();
+        let item = Item::Volume(Channel::Bgm);
+
+        settings.set_level(item, 0.427);
+        assert_eq!(settings.value(item), "43%");
+
+        settings.change(item, true);
+        assert_eq!(settings.value(item), "44%");
+        settings.change(item, false);
+        settings.change(item, false);
+        assert_eq!(settings.value(item), "42%");
+
+        settings.set_level(item, 1.5);
+        assert_eq!(settings.volumes.bgm, 1.0);
+        settings.change(item, true);
+        assert_eq!(settings.volumes.bgm, 1.0);
+        settings.set_level(item, -1.0);
+        assert_eq!(settings.volumes.bgm, 0.0);
+    }
+
+    #[test]
+    fn set_text_speed_level() {
+        let mut settings = Settings::default();
+
+        settings.set_level(Item::TextSpeed, 0.0);
+        assert_eq!(settings.text_speed, MIN_TEXT_SPEED);
+        settings.set_level(Item::TextSpeed, 0.5);
+        assert_eq!(settings.text_speed, 103.0);
+        assert_eq!(settings.value(Item::TextSpeed), "103 chars/s");
+
+        //Rightmost position shows text at once
+        settings.set_level(Item::TextSpeed, 1.0);
+        assert_eq!(settings.text_speed, 0.0);
+        assert_eq!(settings.level(Item::TextSpeed), Some(1.0));
+        assert_eq!(settings.value(Item::TextSpeed), "Instant");
+        settings.change(Item::TextSpeed, false);
+        assert_eq!(settings.text_speed, 198.0);
+
+        //Small step still changes speed
+        settings.text_speed = 40.0;
+        settings.change(Item::TextSpeed, true);
+        assert_eq!(settings.text_speed, 42.0);
+    }
+
+    #[test]
+    fn step_auto_delay() {
+        let mut settings = Settings::default();
+
+        settings.auto_delay = 0.05;
+        settings.change(Item::AutoDelay, true);
+        assert_eq!(settings.value(Item::AutoDelay), "0.052s");
+        settings.change(Item::AutoDelay, false);
+        settings.change(Item::AutoDelay, false);
+        assert_eq!(settings.value(Item::AutoDelay), "0.048s");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::game::components;
+use crate::game::components::ui::backlog::VISIBLE_ENTRIES;
+use crate::game::script;
+use crate::game::input;
+
+use super::{GameData, Base};
+
+///Dialogue history screen.
+///
+///Pushed on top of game and pops itself when closed.
+#[derive(Default)]
+pub struct Backlog {
+    //Number of newest entries scrolled out of view
+    offset: usize,
+    ui: Option<components::ui::Backlog>,
+}
+
+impl Backlog {
+    fn ui(&self) -> &components::ui::Backlog {
+        match self.ui.as_ref() {
+            Some(ui) => ui,
+            None => unreach!()
+        }
+    }
+
+    ///Scrolls history by number of entries, positive values move to older entries.
+    ///
+    ///Returns whether screen should be closed, which happens when scrolled past newest entry.
+    fn scroll(&mut self, world: &mut amethyst::prelude::World, by: isize) -> bool {
+        let history = world.read_resource::<script::History>().clone();
+        let max = history.len().saturating_sub(VISIBLE_ENTRIES);
+
+        if by < 0 && self.offset == 0 {
+            return true;
+        }
+
+        self.offset = if by < 0 {
+            self.offset.saturating_sub(-by as usize)
+        } else {
+            std::cmp::min(self.offset + by as usize, max)
+        };
+
+        self.ui().refresh(world, &history, self.offset);
+        false
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Backlog {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Backlog::new(&mut data.world, &res) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load backlog screen layout: {}", error);
+                components::ui::Backlog::builtin(&mut data.world, &res)
+            }
+        };
+
+        let history = data.world.read_resource::<script::History>().clone();
+        ui.refresh(&mut data.world, &history, self.offset);
+        self.ui = Some(ui);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, mut data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => {
+                let scroll = if let Some(delta) = input::mouse_wheel(&event) {
+                    if delta > 0.0 {
+                        1
+                    } else if delta < 0.0 {
+                        -1
+                    } else {
+                        0
+                    }
+                } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Up) {
+                    1
+                } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Down) {
+                    -1
+                } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::PageUp) {
+                    VISIBLE_ENTRIES as isize
+                } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::PageDown) {
+                    -(VISIBLE_ENTRIES as isize)
+                } else {
+                    0
+                };
+
+                if amethyst::input::is_close_requested(&event) {
+                    amethyst::Trans::Quit
+                } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Escape) || amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::B) || input::is_stage_click(&event, amethyst::renderer::MouseButton::Right, &data.world) {
+                    amethyst::Trans::Pop
+                } else if scroll != 0 && self.scroll(&mut data.world, scroll) {
+                    amethyst::Trans::Pop
+                } else {
+                    amethyst::Trans::None
+                }
+            },
+            _ => amethyst::Trans::None,
+        }
+    }
+
+    fn update(&mut self, state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update(Base, &state.world.res);
+        amethyst::Trans::None
+    }
+}
@@ -0,0 +1,833 @@
+use amethyst::ecs::prelude::{Dispatcher, DispatcherBuilder, Resources, System};
+use amethyst::core::bundle::SystemBundle;
+
+use crate::game::components;
+use crate::game::components::transition::{self, Transition};
+use crate::game::components::ui::UiComponent;
+use crate::game::script;
+use crate::game::save;
+use crate::game::audio;
+use crate::game::input;
+
+use std::collections::HashSet;
+use std::time;
+
+mod slots;
+mod backlog;
+mod settings;
+mod pause;
+
+pub use self::slots::Slots;
+pub use self::backlog::Backlog;
+pub use self::settings::Settings;
+pub use self::pause::Pause;
+
+///Requests game beneath overlay state to return to title screen.
+#[derive(Default)]
+pub struct ReturnToTitle(pub bool);
+
+///Writes seen lines, so that they are not lost if game doesn't exit cleanly.
+///
+///Done when game is left and on every save made by player.
+fn store_seen(world: &amethyst::prelude::World) {
+    if let Err(error) = world.read_resource::<save::Seen>().store() {
+        error!("Unable to store seen lines: {}", error);
+    }
+}
+
+pub trait DispatcherSelector<Arg, Res> {
+    fn select<'c>(builder: &'c mut Arg) -> &'c mut Res;
+}
+
+pub struct Base;
+impl<'a, 'b> DispatcherSelector<GameData<'a, 'b>, Dispatcher<'a, 'b>> for Base {
+    fn select<'c>(builder: &'c mut GameData<'a, 'b>) -> &'c mut Dispatcher<'a, 'b> {
+        &mut builder.basic
+    }
+}
+impl<'a, 'b> DispatcherSelector<GameDataBuilder<'a, 'b>, DispatcherBuilder<'a, 'b>> for Base {
+    fn select<'c>(builder: &'c mut GameDataBuilder<'a, 'b>) -> &'c mut DispatcherBuilder<'a, 'b> {
+        &mut builder.basic
+    }
+}
+
+pub struct Adv;
+impl<'a, 'b> DispatcherSelector<GameData<'a, 'b>, Dispatcher<'a, 'b>> for Adv {
+    fn select<'c>(builder: &'c mut GameData<'a, 'b>) -> &'c mut Dispatcher<'a, 'b> {
+        &mut builder.adv
+    }
+}
+impl<'a, 'b> DispatcherSelector<GameDataBuilder<'a, 'b>, DispatcherBuilder<'a, 'b>> for Adv {
+    fn select<'c>(builder: &'c mut GameDataBuilder<'a, 'b>) -> &'c mut DispatcherBuilder<'a, 'b> {
+        &mut builder.adv
+    }
+}
+
+///Set of dispatchers that is run by `GameData::update`.
+///
+///Overlay states run only `Base`, so that scene beneath them is frozen,
+///while `(Base, Adv)` runs everything.
+pub trait Dispatch {
+    fn dispatch<'a, 'b>(data: &mut GameData<'a, 'b>, res: &Resources);
+}
+
+impl Dispatch for Base {
+    fn dispatch<'a, 'b>(data: &mut GameData<'a, 'b>, res: &Resources) {
+        <Self as DispatcherSelector<GameData<'a, 'b>, Dispatcher<'a, 'b>>>::select(data).dispatch(res)
+    }
+}
+
+impl Dispatch for Adv {
+    fn dispatch<'a, 'b>(data: &mut GameData<'a, 'b>, res: &Resources) {
+        <Self as DispatcherSelector<GameData<'a, 'b>, Dispatcher<'a, 'b>>>::select(data).dispatch(res)
+    }
+}
+
+impl<A: Dispatch, B: Dispatch> Dispatch for (A, B) {
+    fn dispatch<'a, 'b>(data: &mut GameData<'a, 'b>, res: &Resources) {
+        A::dispatch(data, res);
+        B::dispatch(data, res);
+    }
+}
+
+pub struct GameData<'a, 'b> {
+    basic: Dispatcher<'a, 'b>,
+    adv: Dispatcher<'a, 'b>,
+}
+
+impl<'a, 'b> GameData<'a, 'b> {
+    ///Runs dispatchers in order, e.g. `update(Base, ..)` or `update((Base, Adv), ..)`.
+    pub fn update<D: Dispatch>(&mut self, _: D, res: &Resources) {
+        D::dispatch(self, res)
+    }
+}
+
+#[derive(Default)]
+pub struct GameDataBuilder<'a, 'b> {
+    basic: DispatcherBuilder<'a, 'b>,
+    adv: DispatcherBuilder<'a, 'b>,
+}
+
+impl<'a, 'b> GameDataBuilder<'a, 'b> {
+    pub fn with_bundle<D: DispatcherSelector<Self, DispatcherBuilder<'a, 'b>>, B: SystemBundle<'a, 'b>>(mut self, _: D, bundle: B) -> amethyst::core::bundle::Result<Self> {
+        bundle.build(D::select(&mut self))?;
+        Ok(self)
+    }
+
+    pub fn with<D: DispatcherSelector<Self, DispatcherBuilder<'a, 'b>>, S>(mut self, _: D, system: S, name: &str, deps: &[&str]) -> Self where for<'c> S: System<'c> + Send + 'a {
+        D::select(&mut self).add(system, name, deps);
+        self
+    }
+}
+
+impl<'a, 'b> amethyst::DataInit<GameData<'a, 'b>> for GameDataBuilder<'a, 'b> {
+    fn build(self, world: &mut amethyst::prelude::World) -> GameData<'a, 'b> {
+        let pool = world.read_resource::<amethyst::core::ArcThreadPool>().clone();
+
+        let mut basic = self.basic.with_pool(pool.clone()).build();
+        let mut adv = self.adv.with_pool(pool.clone()).build();
+        basic.setup(&mut world.res);
+        adv.setup(&mut world.res);
+
+        GameData {
+            basic,
+            adv,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Menu {
+    ui: Option<components::ui::Menu>,
+    //Game to start once screen is covered
+    next: Option<Game>,
+}
+
+impl Menu {
+    fn start(&mut self, world: &mut amethyst::prelude::World, game: Game) {
+        world.write_resource::<Transition>().cover(transition::Kind::Fade(transition::BLACK), transition::DEFAULT_DURATION);
+        self.next = Some(game);
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Menu {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+        components::characters::Characters::fetch(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Menu::new(&mut data.world, &res) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load title screen layout: {}", error);
+                components::ui::Menu::builtin(&mut data.world, &res)
+            }
+        };
+        self.ui = Some(ui);
+        data.world.write_resource::<Transition>().reveal(transition::DEFAULT_DURATION);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, mut data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => if amethyst::input::is_close_requested(&event) {
+                amethyst::Trans::Quit
+            } else {
+                amethyst::Trans::None
+            },
+            //Menu is not interactive while screen is covered
+            amethyst::StateEvent::Ui(_) if data.world.read_resource::<Transition>().is_active() => amethyst::Trans::None,
+            amethyst::StateEvent::Ui(event) => match event.event_type {
+                amethyst::ui::UiEventType::ClickStop => {
+                    //Owned, as starting game needs to mutate menu
+                    let target = match self.ui.as_ref() {
+                        Some(ui) => ui.find(event.target).map(str::to_owned),
+                        None => unreach!()
+                    };
+
+                    //TODO: we actually get two click events?
+                    info!("Click by {:?}", target);
+
+                    match target.as_ref().map(String::as_str) {
+                        Some("exit") => amethyst::Trans::Quit,
+                        Some("new_game") => {
+                            self.start(&mut data.world, Game::default());
+                            amethyst::Trans::None
+                        },
+                        Some("load_game") => amethyst::Trans::Push(Box::new(Slots::load())),
+                        Some("settings") => amethyst::Trans::Push(Box::new(Settings::default())),
+                        _ => amethyst::Trans::None,
+                    }
+                },
+                _ => amethyst::Trans::None
+            },
+        }
+    }
+
+    fn update(&mut self, state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update(Base, &state.world.res);
+
+        if let Some(save) = state.world.write_resource::<save::Pending>().0.take() {
+            self.start(state.world, Game::load(save));
+        }
+
+        if !state.world.read_resource::<Transition>().is_covered() {
+            return amethyst::Trans::None;
+        }
+
+        match self.next.take() {
+            Some(game) => amethyst::Trans::Switch(Box::new(game)),
+            None => amethyst::Trans::None,
+        }
+    }
+}
+
+///State to switch to once screen is covered.
+enum Next {
+    Menu,
+    Load(save::Save),
+}
+
+const CLICK_BOUNCE_TIMEOUT: time::Duration = time::Duration::from_secs(1);
+
+pub struct Game {
+    ui: Option<components::ui::Adv>,
+    stage: Option<components::stage::Stage>,
+    camera: Option<components::camera::Camera>,
+    choices: Option<components::ui::Choices>,
+    //Save to restore on start
+    save: Option<save::Save>,
+    last_click_inst: time::Instant,
+    //Auto mode and time passed since line is revealed
+    auto: bool,
+    auto_timer: f32,
+    //Skip mode toggled by key, it can also be activated by holding Ctrl
+    skip: bool,
+    //Whether current line was seen before it is displayed
+    line_seen: bool,
+    //Animations started by script
+    tweens: HashSet<amethyst::ecs::Entity>,
+    tween_reader: Option<amethyst::shrev::ReaderId<components::tween::TweenEvent>>,
+    //Commands to apply once screen is covered and duration of reveal
+    deferred: Option<(f32, Vec<script::Command>)>,
+    next: Option<Next>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            ui: None,
+            stage: None,
+            camera: None,
+            choices: None,
+            save: None,
+            last_click_inst: time::Instant::now(),
+            auto: false,
+            auto_timer: 0.0,
+            skip: false,
+            line_seen: false,
+            tweens: HashSet::new(),
+            tween_reader: None,
+            deferred: None,
+            next: None,
+        }
+    }
+}
+
+impl Game {
+    ///Creates game that continues from save.
+    pub fn load(save: save::Save) -> Self {
+        Self {
+            save: Some(save),
+            ..Self::default()
+        }
+    }
+
+    ///Creates snapshot of current playthrough.
+    pub fn snapshot(&mut self, world: &mut amethyst::prelude::World) -> save::Save {
+        let (script, position, chapter, preview) = {
+            let runner = world.read_resource::<script::Runner>();
+            let preview = runner.line().map(script::Line::display).unwrap_or_default();
+            (runner.script_name().to_owned(), runner.current(), runner.chapter().map(str::to_owned), preview)
+        };
+        let text_hidden = self.ui_mut().text.is_hidden(world);
+        let stage = self.stage_mut();
+
+        save::Save {
+            version: save::VERSION,
+            timestamp: save::Save::now(),
+            script,
+            position,
+            chapter,
+            preview,
+            variables: world.read_resource::<script::Variables>().clone(),
+            characters: stage.characters(),
+            background: stage.background_name().map(str::to_owned),
+            background_fit: stage.background_fit(),
+            bgm: world.read_resource::<audio::Audio>().bgm().map(str::to_owned),
+            text_hidden,
+            history: world.read_resource::<script::History>().clone(),
+        }
+    }
+
+    ///Restores stage and script state from save.
+    fn restore(&mut self, world: &mut amethyst::prelude::World, save: save::Save) -> Result<script::Runner, script::Error> {
+        let script = script::Script::load(&save.script)?;
+        let characters = components::characters::Characters::fetch(world);
+
+        if let Some(background) = save.background.as_ref() {
+            self.stage_mut().background(world, Some(background), save.background_fit, None);
+        }
+
+        if let Some(bgm) = save.bgm.as_ref() {
+            world.write_resource::<audio::Audio>().play(audio::Channel::Bgm, bgm, true, None);
+        }
+
+        for character in save.characters.iter() {
+            self.stage_mut().show(world, &characters, &character.name, character.expression.as_ref().map(String::as_str), Some(character.position), None);
+        }
+
+        world.add_resource(save.variables);
+        Ok(script::Runner::restore(script, save.position, save.chapter))
+    }
+
+    pub fn is_close_click_bounced(&mut self) -> bool {
+        let now = time::Instant::now();
+        let duration = now.duration_since(self.last_click_inst);
+        self.last_click_inst = now;
+
+        CLICK_BOUNCE_TIMEOUT >= duration
+    }
+
+    pub fn ui_mut(&mut self) -> &mut components::ui::Adv {
+        match self.ui.as_mut() {
+            Some(ui) => ui,
+            None => unreach!()
+        }
+    }
+
+    pub fn stage_mut(&mut self) -> &mut components::stage::Stage {
+        match self.stage.as_mut() {
+            Some(stage) => stage,
+            None => unreach!()
+        }
+    }
+
+    ///Switches auto mode on/off.
+    pub fn toggle_auto(&mut self, world: &mut amethyst::prelude::World) {
+        self.auto = !self.auto;
+        self.auto_timer = 0.0;
+
+        let res = components::ui::Resources::fetch(world);
+        self.ui_mut().text.set_auto(world, &res, self.auto);
+    }
+
+    ///Advances to the next line once current one is revealed and its delay is passed.
+    ///
+    ///Auto mode waits while choice is pending, animations, transition or voice are playing or text window is hidden.
+    fn update_auto(&mut self, world: &mut amethyst::prelude::World) {
+        let delay = {
+            let runner = world.read_resource::<script::Runner>();
+            match runner.line() {
+                Some(line) if runner.choices().is_none() && !runner.is_waiting() => world.read_resource::<components::typewriter::AutoDelay>().delay(line.text.chars().count()),
+                _ => {
+                    self.auto_timer = 0.0;
+                    return;
+                }
+            }
+        };
+
+        if self.ui_mut().text.is_hidden(world) || self.ui_mut().text.is_typing(world) || world.read_resource::<Transition>().is_active() || world.read_resource::<audio::Audio>().is_playing(audio::Channel::Voice) {
+            self.auto_timer = 0.0;
+            return;
+        }
+
+        self.auto_timer += world.read_resource::<amethyst::core::timing::Time>().delta_seconds();
+        if self.auto_timer >= delay {
+            self.advance(world);
+        }
+    }
+
+    ///Returns whether skip mode is active.
+    fn is_skipping(&self, world: &amethyst::prelude::World) -> bool {
+        if self.skip {
+            return true;
+        }
+
+        let input = world.read_resource::<amethyst::input::InputHandler<String, String>>();
+        input.key_is_down(amethyst::renderer::VirtualKeyCode::LControl) || input.key_is_down(amethyst::renderer::VirtualKeyCode::RControl)
+    }
+
+    ///Skips current line.
+    ///
+    ///Skip mode stops on choices and on unseen lines, unless `SkipUnread` is set.
+    fn update_skip(&mut self, world: &mut amethyst::prelude::World) {
+        let is_choice = world.read_resource::<script::Runner>().choices().is_some();
+        if is_choice || (!self.line_seen && !world.read_resource::<save::SkipUnread>().0) {
+            self.skip = false;
+            return;
+        }
+
+        self.ui_mut().text.complete_text(world);
+        self.advance(world);
+    }
+
+    ///Tracks animations started by script and resumes script once they are finished.
+    fn update_tweens(&mut self, world: &mut amethyst::prelude::World) {
+        let reader = match self.tween_reader.as_mut() {
+            Some(reader) => reader,
+            None => unreach!(),
+        };
+
+        for event in world.read_resource::<amethyst::shrev::EventChannel<components::tween::TweenEvent>>().read(reader) {
+            match event {
+                components::tween::TweenEvent::Finished(entity) => self.tweens.remove(entity),
+            };
+        }
+        //Character can be removed from stage before its animation is finished
+        self.tweens.retain(|entity| world.is_alive(*entity));
+
+        if self.tweens.is_empty() && self.deferred.is_none() && world.read_resource::<script::Runner>().is_waiting() {
+            world.write_resource::<script::Runner>().resume();
+            self.advance(world);
+        }
+    }
+
+    ///Finishes all animations started by script at once.
+    fn finish_tweens(&mut self, world: &mut amethyst::prelude::World) {
+        let mut tweens = world.write_storage::<components::tween::Tween>();
+
+        for entity in self.tweens.iter() {
+            if let Some(tween) = tweens.get_mut(*entity) {
+                tween.finish();
+            }
+        }
+    }
+
+    ///Advances script to the next dialogue line.
+    ///
+    ///If text window is hidden, then it is shown instead.
+    ///If line is not fully revealed yet, then it is completed instead.
+    ///If script waits for animations, then they are finished instead.
+    ///If screen transition is in progress, then it is fast-forwarded instead.
+    pub fn advance(&mut self, world: &mut amethyst::prelude::World) {
+        self.auto_timer = 0.0;
+
+        if world.read_resource::<Transition>().is_active() {
+            world.write_resource::<Transition>().finish();
+            return;
+        } else if world.read_resource::<script::Runner>().is_waiting() {
+            self.finish_tweens(world);
+            return;
+        } else if self.ui_mut().text.is_hidden(world) {
+            self.ui_mut().text.toggle_hide(world);
+            return;
+        } else if self.ui_mut().text.complete_text(world) {
+            return;
+        }
+
+        if world.read_resource::<audio::StopVoice>().0 {
+            world.write_resource::<audio::Audio>().stop(audio::Channel::Voice, None);
+        }
+
+        let commands = {
+            let mut vars = world.write_resource::<script::Variables>();
+            match world.write_resource::<script::Runner>().advance(&mut vars) {
+                Ok(commands) => commands,
+                //Runner is finished on error, so we'll return to menu
+                Err(error) => {
+                    error!("Script error: {}", error);
+                    return;
+                }
+            }
+        };
+
+        if self.apply(world, commands) {
+            self.display(world);
+        }
+    }
+
+    ///Applies stage commands of script.
+    ///
+    ///Returns `false` if command needs screen to be covered first.
+    ///In this case remaining commands are applied by `update_transition`.
+    fn apply(&mut self, world: &mut amethyst::prelude::World, commands: Vec<script::Command>) -> bool {
+        let characters = components::characters::Characters::fetch(world);
+        let mut commands = commands.into_iter();
+
+        while let Some(command) = commands.next() {
+            //Dissolve is done by stage, other transitions cover whole screen
+            let dissolve = match command.transition() {
+                Some(script::Transition { kind: transition::Kind::Dissolve, duration }) => Some(duration),
+                Some(script::Transition { kind, duration }) => {
+                    let mut screen = world.write_resource::<Transition>();
+                    if !screen.is_covered() {
+                        screen.cover(kind, duration / 2.0);
+                        let mut deferred = vec![command];
+                        deferred.extend(commands);
+                        self.deferred = Some((duration / 2.0, deferred));
+                        return false;
+                    }
+                    None
+                },
+                None => None,
+            };
+
+            match command {
+                script::Command::Show { name, expression, position, .. } => self.stage_mut().show(world, &characters, &name, expression.as_ref().map(String::as_str), position, dissolve),
+                script::Command::Move { name, position, motion } => if let Some(entity) = self.stage_mut().move_to(world, &name, position, motion) {
+                    self.tweens.insert(entity);
+                },
+                script::Command::Hide { name, .. } => self.stage_mut().hide(world, &name, dissolve),
+                script::Command::Background { name, fit, .. } => self.stage_mut().background(world, name.as_ref().map(String::as_str), fit, dissolve),
+                script::Command::Play { channel, file, looping, fade } => world.write_resource::<audio::Audio>().play(channel, &file, looping, fade),
+                script::Command::Stop { channel, fade } => world.write_resource::<audio::Audio>().stop(channel, fade),
+                script::Command::Volume { channel, volume } => world.write_resource::<audio::Audio>().set_volume(channel, volume),
+                //Runner handles the rest on its own
+                _ => unreach!(),
+            }
+        }
+
+        true
+    }
+
+    ///Applies deferred commands once screen is covered and reveals it.
+    fn update_transition(&mut self, world: &mut amethyst::prelude::World) {
+        if !world.read_resource::<Transition>().is_covered() {
+            return;
+        }
+
+        if let Some((duration, commands)) = self.deferred.take() {
+            //Screen is covered, so all commands are applied at once
+            self.apply(world, commands);
+            world.write_resource::<Transition>().reveal(duration);
+            self.display(world);
+        }
+    }
+
+    ///Covers screen before switching to the next state.
+    fn leave(&mut self, world: &mut amethyst::prelude::World, next: Next) {
+        world.write_resource::<Transition>().cover(transition::Kind::Fade(transition::BLACK), transition::DEFAULT_DURATION);
+        self.next = Some(next);
+    }
+
+    ///Displays current line or choices of script.
+    fn display(&mut self, world: &mut amethyst::prelude::World) {
+        let characters = components::characters::Characters::fetch(world);
+
+        //Keep last line on screen while animations are playing
+        if world.read_resource::<script::Runner>().is_waiting() {
+            return;
+        }
+
+        let choices = world.read_resource::<script::Runner>().choices().map(|choices| choices.to_vec());
+        if let Some(choices) = choices {
+            if self.choices.is_none() {
+                let res = components::ui::Resources::fetch(world);
+                self.choices = Some(components::ui::Choices::new(world, &res, &choices));
+            }
+            //Keep last line on screen while player makes choice
+            return;
+        }
+
+        let (speaker, text, speed) = match world.read_resource::<script::Runner>().line() {
+            Some(line) => {
+                //Script refers to character by id, which is also shown if character is not defined
+                let speaker = line.speaker.as_ref().map(|id| match characters.get(id) {
+                    Some(character) => (character.name.clone(), character.color),
+                    None => (id.clone(), components::ui::DEFAULT_NAME_COLOR),
+                });
+
+                self.line_seen = world.write_resource::<save::Seen>().insert(line.id);
+                world.write_resource::<script::History>().push(script::HistoryEntry::Line {
+                    speaker: speaker.as_ref().map(|(name, _)| name.clone()),
+                    text: line.text.clone(),
+                });
+                (speaker, line.text.clone(), line.speed)
+            },
+            None => (None, String::new(), None),
+        };
+        match speaker {
+            Some((name, color)) => self.ui_mut().text.set_speaker(world, Some(&name), color),
+            None => self.ui_mut().text.set_speaker(world, None, components::ui::DEFAULT_NAME_COLOR),
+        }
+        self.ui_mut().text.type_text(world, text, speed);
+        self.play_voice(world);
+    }
+
+    ///Plays voice of current line, if it has one.
+    fn play_voice(&mut self, world: &mut amethyst::prelude::World) {
+        let characters = components::characters::Characters::fetch(world);
+        let (voice, level) = match world.read_resource::<script::Runner>().line() {
+            Some(script::Line { voice: Some(voice), speaker, .. }) => {
+                let level = speaker.as_ref().and_then(|id| characters.get(id)).map(|character| character.voice_volume).unwrap_or(1.0);
+                (voice.clone(), level)
+            },
+            _ => return,
+        };
+
+        world.write_resource::<audio::Audio>().play_at(audio::Channel::Voice, &voice, false, None, level);
+    }
+
+    ///Picks choice option and continues script.
+    pub fn choose(&mut self, world: &mut amethyst::prelude::World, idx: usize) {
+        let text = match world.read_resource::<script::Runner>().choices().and_then(|choices| choices.get(idx)) {
+            Some(choice) => choice.text.clone(),
+            None => return,
+        };
+
+        if !world.write_resource::<script::Runner>().choose(idx) {
+            return;
+        }
+
+        world.write_resource::<script::History>().push(script::HistoryEntry::Choice(text));
+
+        if let Some(choices) = self.choices.take() {
+            choices.destroy(world);
+        }
+
+        self.advance(world);
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Game {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Adv::new(&mut data.world, &res) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load dialogue screen layout: {}", error);
+                components::ui::Adv::builtin(&mut data.world, &res)
+            }
+        };
+        self.ui = Some(ui);
+        self.stage = Some(components::stage::Stage::new());
+
+        self.camera = Some(components::camera::Camera::new(&mut data.world));
+
+        data.world.add_resource(script::Variables::default());
+        data.world.add_resource(script::History::default());
+        data.world.add_resource(ReturnToTitle::default());
+        self.tween_reader = Some(data.world.write_resource::<amethyst::shrev::EventChannel<components::tween::TweenEvent>>().register_reader());
+
+        let (runner, text_hidden, history) = match self.save.take() {
+            Some(mut save) => {
+                let text_hidden = save.text_hidden;
+                let history = std::mem::replace(&mut save.history, script::History::default());
+                (self.restore(&mut data.world, save), text_hidden, Some(history))
+            },
+            None => (script::Script::load(script::MAIN).map(script::Runner::new), false, None),
+        };
+
+        //On failure runner is empty and we'll return to menu right away
+        let runner = match runner {
+            Ok(runner) => runner,
+            Err(error) => {
+                error!("Unable to start game: {}", error);
+                script::Runner::default()
+            }
+        };
+        //Rule transitions are built ahead so that stage commands don't need to decode masks
+        data.world.write_resource::<transition::Rules>().prepare(runner.script(),
+                                                                 &data.world.read_resource::<amethyst::assets::Loader>(),
+                                                                 &data.world.read_resource::<amethyst::assets::AssetStorage<amethyst::renderer::Texture>>());
+        data.world.add_resource(runner);
+
+        self.advance(&mut data.world);
+
+        //Saved history already contains restored line
+        if let Some(history) = history {
+            data.world.add_resource(history);
+        }
+
+        //Hide text window only after line is displayed
+        if text_hidden {
+            self.ui_mut().text.toggle_hide(&mut data.world);
+        }
+
+        data.world.write_resource::<Transition>().reveal(transition::DEFAULT_DURATION);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        if let Some(stage) = self.stage.take() {
+            stage.destroy(&mut data.world);
+        }
+
+        if let Some(camera) = self.camera.take() {
+            camera.destroy(&mut data.world);
+        }
+
+        if let Some(choices) = self.choices.take() {
+            choices.destroy(&mut data.world);
+        }
+
+        data.world.write_resource::<audio::Audio>().stop_all();
+
+        store_seen(&data.world);
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, mut data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => if amethyst::input::is_close_requested(&event) {
+                amethyst::Trans::Quit
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Escape) || input::is_stage_click(&event, amethyst::renderer::MouseButton::Right, &data.world) {
+                let snapshot = self.snapshot(&mut data.world);
+                amethyst::Trans::Push(Box::new(Pause::new(snapshot)))
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::B) || input::mouse_wheel(&event).map(|delta| delta > 0.0).unwrap_or(false) {
+                amethyst::Trans::Push(Box::new(Backlog::default()))
+            } else if input::is_fullscreen_toggle(&event) {
+                //Handled by window system, Alt+Enter shouldn't advance dialogue
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Space) || amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Return) {
+                self.advance(&mut data.world);
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::V) {
+                self.play_voice(&mut data.world);
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::A) {
+                self.toggle_auto(&mut data.world);
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Tab) {
+                self.skip = !self.skip;
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::H) {
+                self.ui_mut().text.toggle_hide(&mut data.world);
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::F5) {
+                let snapshot = self.snapshot(&mut data.world);
+                match snapshot.write(save::QUICK_SLOT) {
+                    Ok(()) => info!("Quick save is done"),
+                    Err(error) => error!("Unable to save: {}", error),
+                }
+                store_seen(&data.world);
+                amethyst::Trans::None
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::S) {
+                let snapshot = self.snapshot(&mut data.world);
+                amethyst::Trans::Push(Box::new(Slots::save(snapshot)))
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::L) {
+                amethyst::Trans::Push(Box::new(Slots::load()))
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::O) {
+                amethyst::Trans::Push(Box::new(Settings::default()))
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::F9) {
+                match save::Save::read(save::QUICK_SLOT) {
+                    Ok(save) => self.leave(&mut data.world, Next::Load(save)),
+                    Err(error) => error!("Unable to load: {}", error),
+                }
+                amethyst::Trans::None
+            } else {
+                amethyst::Trans::None
+            },
+            amethyst::StateEvent::Ui(event) => match event.event_type {
+                amethyst::ui::UiEventType::Click => {
+                    if event.target == self.ui_mut().text.close && !self.is_close_click_bounced() {
+                        info!("Close text window!");
+                        self.ui_mut().text.toggle_hide(&mut data.world);
+                    }
+
+                    amethyst::Trans::None
+                },
+                amethyst::ui::UiEventType::ClickStop => {
+                    if event.target == self.ui_mut().text.window {
+                        self.advance(&mut data.world);
+                    } else if event.target == self.ui_mut().text.auto {
+                        self.toggle_auto(&mut data.world);
+                    } else if let Some(idx) = self.choices.as_ref().and_then(|choices| choices.find(event.target)) {
+                        self.choose(&mut data.world, idx);
+                    }
+
+                    amethyst::Trans::None
+                },
+                _ => amethyst::Trans::None
+            }
+        }
+    }
+
+    fn update(&mut self, state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update((Base, Adv), &state.world.res);
+
+        self.update_tweens(state.world);
+        self.update_transition(state.world);
+        self.stage_mut().update(state.world);
+
+        if self.is_skipping(state.world) {
+            self.update_skip(state.world);
+        } else if self.auto {
+            self.update_auto(state.world);
+        }
+
+        if let Some(save) = state.world.write_resource::<save::Pending>().0.take() {
+            self.leave(state.world, Next::Load(save));
+        } else if std::mem::replace(&mut state.world.write_resource::<ReturnToTitle>().0, false) {
+            self.leave(state.world, Next::Menu);
+        } else if self.next.is_none() && state.world.read_resource::<script::Runner>().is_finished() {
+            self.leave(state.world, Next::Menu);
+        }
+
+        if self.deferred.is_some() || !state.world.read_resource::<Transition>().is_covered() {
+            return amethyst::Trans::None;
+        }
+
+        match self.next.take() {
+            Some(Next::Menu) => amethyst::Trans::Switch(Box::new(Menu::default())),
+            Some(Next::Load(save)) => amethyst::Trans::Switch(Box::new(Game::load(save))),
+            None => amethyst::Trans::None,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::game::components;
+use crate::game::save;
+use crate::game::input;
+
+use super::{GameData, Base, Slots, Settings, ReturnToTitle};
+
+///In-game menu.
+///
+///Pushed on top of game, which is frozen as only `Base` dispatcher is run.
+///Loaded save and return to title are passed to the game beneath via resources.
+pub struct Pause {
+    //Snapshot of game at the moment it is paused
+    snapshot: save::Save,
+    ui: Option<components::ui::Pause>,
+}
+
+impl Pause {
+    pub fn new(snapshot: save::Save) -> Self {
+        Self {
+            snapshot,
+            ui: None,
+        }
+    }
+
+    fn ui(&self) -> &components::ui::Pause {
+        match self.ui.as_ref() {
+            Some(ui) => ui,
+            None => unreach!()
+        }
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Pause {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Pause::new(&mut data.world, &res) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load pause screen layout: {}", error);
+                components::ui::Pause::builtin(&mut data.world, &res)
+            }
+        };
+        self.ui = Some(ui);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => if amethyst::input::is_close_requested(&event) {
+                amethyst::Trans::Quit
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Escape) || input::is_stage_click(&event, amethyst::renderer::MouseButton::Right, &data.world) {
+                amethyst::Trans::Pop
+            } else {
+                amethyst::Trans::None
+            },
+            amethyst::StateEvent::Ui(event) => match event.event_type {
+                amethyst::ui::UiEventType::ClickStop => {
+                    match self.ui().find(event.target) {
+                        Some("resume") => amethyst::Trans::Pop,
+                        Some("save") => amethyst::Trans::Push(Box::new(Slots::save(self.snapshot.clone()))),
+                        Some("load") => amethyst::Trans::Push(Box::new(Slots::load())),
+                        Some("settings") => amethyst::Trans::Push(Box::new(Settings::default())),
+                        Some("title_screen") => {
+                            data.world.write_resource::<ReturnToTitle>().0 = true;
+                            amethyst::Trans::Pop
+                        },
+                        Some("quit") => amethyst::Trans::Quit,
+                        _ => amethyst::Trans::None,
+                    }
+                },
+                _ => amethyst::Trans::None
+            },
+        }
+    }
+
+    fn update(&mut self, state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update(Base, &state.world.res);
+
+        //Save picked on load screen is started by game
+        if state.world.read_resource::<save::Pending>().0.is_some() {
+            amethyst::Trans::Pop
+        } else {
+            amethyst::Trans::None
+        }
+    }
+}
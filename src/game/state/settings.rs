@@ -0,0 +1,156 @@
+use crate::game::components;
+use crate::game::settings;
+use crate::game::input;
+
+use super::{GameData, Base};
+
+///Settings screen.
+///
+///Pushed on top of current state and pops itself when closed.
+///Changes are applied at once and stored when screen is closed.
+#[derive(Default)]
+pub struct Settings {
+    ui: Option<components::ui::Settings>,
+    //Window mode is toggled by window system during update
+    refresh: bool,
+    //Slider that follows cursor while left mouse button is held
+    slide: Option<settings::Item>,
+}
+
+impl Settings {
+    fn ui(&self) -> &components::ui::Settings {
+        match self.ui.as_ref() {
+            Some(ui) => ui,
+            None => unreach!()
+        }
+    }
+
+    fn change(&mut self, world: &mut amethyst::prelude::World, item: settings::Item, forward: bool) {
+        self.modify(world, item, |settings| settings.change(item, forward));
+    }
+
+    ///Moves slider to cursor, until mouse button is released.
+    fn slide(&mut self, world: &mut amethyst::prelude::World) {
+        let item = match self.slide {
+            Some(item) => item,
+            None => return,
+        };
+
+        let level = match input::held_cursor(world, amethyst::renderer::MouseButton::Left) {
+            Some((x, _)) => self.ui().slider_level(world, item, x),
+            None => {
+                self.slide = None;
+                return;
+            }
+        };
+
+        if let Some(level) = level {
+            //Applied only when rounded value changes, as cursor is checked every frame
+            let changed = {
+                let settings = world.read_resource::<settings::Settings>();
+                let mut moved = settings.clone();
+                moved.set_level(item, level);
+                moved != *settings
+            };
+
+            if changed {
+                self.modify(world, item, |settings| settings.set_level(item, level));
+            }
+        }
+    }
+
+    ///Modifies setting with `update` and applies result.
+    fn modify<F: FnOnce(&mut settings::Settings)>(&mut self, world: &mut amethyst::prelude::World, item: settings::Item, update: F) {
+        let settings = {
+            let mut settings = world.write_resource::<settings::Settings>();
+            update(&mut *settings);
+            settings.clone()
+        };
+
+        settings.apply(world);
+        if item.is_window() {
+            settings.apply_window(world);
+        }
+
+        self.ui().refresh(world, &settings);
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Settings {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Settings::new(&mut data.world, &res) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load settings screen layout: {}", error);
+                components::ui::Settings::builtin(&mut data.world, &res)
+            }
+        };
+        let settings = data.world.read_resource::<settings::Settings>().clone();
+        ui.refresh(&mut data.world, &settings);
+        self.ui = Some(ui);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        if let Err(error) = data.world.read_resource::<settings::Settings>().store() {
+            error!("Unable to store settings: {}", error);
+        }
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, mut data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => if amethyst::input::is_close_requested(&event) {
+                amethyst::Trans::Quit
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Escape) || input::is_stage_click(&event, amethyst::renderer::MouseButton::Right, &data.world) {
+                amethyst::Trans::Pop
+            } else {
+                self.refresh = input::is_fullscreen_toggle(&event) || self.refresh;
+                amethyst::Trans::None
+            },
+            amethyst::StateEvent::Ui(event) => match event.event_type {
+                //Slider is grabbed on press, so that it can be dragged
+                amethyst::ui::UiEventType::ClickStart => {
+                    if let Some(components::ui::settings::Action::Slide(item)) = self.ui().find(event.target) {
+                        self.slide = Some(item);
+                        self.slide(&mut data.world);
+                    }
+                    amethyst::Trans::None
+                },
+                amethyst::ui::UiEventType::ClickStop => {
+                    if event.target == self.ui().back {
+                        amethyst::Trans::Pop
+                    } else if let Some(components::ui::settings::Action::Change(item, forward)) = self.ui().find(event.target) {
+                        self.change(&mut data.world, item, forward);
+                        amethyst::Trans::None
+                    } else {
+                        amethyst::Trans::None
+                    }
+                },
+                _ => amethyst::Trans::None
+            },
+        }
+    }
+
+    fn update(&mut self, mut state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update(Base, &state.world.res);
+
+        self.slide(&mut state.world);
+
+        if self.refresh {
+            let settings = state.world.read_resource::<settings::Settings>().clone();
+            self.ui().refresh(&mut state.world, &settings);
+            self.refresh = false;
+        }
+
+        amethyst::Trans::None
+    }
+}
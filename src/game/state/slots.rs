@@ -0,0 +1,154 @@
+use crate::game::components;
+use crate::game::components::ui::slots::Mode;
+use crate::game::save;
+use crate::game::input;
+
+use super::{GameData, Base, store_seen};
+
+///Save/Load screen.
+///
+///Pushed on top of current state, and pops itself when closed.
+///Picked save is passed via `save::Pending` to the state beneath.
+pub struct Slots {
+    mode: Mode,
+    page: u32,
+    //Snapshot to write on Save screen
+    snapshot: Option<save::Save>,
+    ui: Option<components::ui::Slots>,
+}
+
+impl Slots {
+    pub fn save(snapshot: save::Save) -> Self {
+        Self {
+            mode: Mode::Save,
+            page: 0,
+            snapshot: Some(snapshot),
+            ui: None,
+        }
+    }
+
+    pub fn load() -> Self {
+        Self {
+            mode: Mode::Load,
+            page: 0,
+            snapshot: None,
+            ui: None,
+        }
+    }
+
+    fn ui(&self) -> &components::ui::Slots {
+        match self.ui.as_ref() {
+            Some(ui) => ui,
+            None => unreach!()
+        }
+    }
+
+    fn turn_page(&mut self, world: &mut amethyst::prelude::World, forward: bool) {
+        self.page = if forward {
+            (self.page + 1) % save::PAGES
+        } else {
+            (self.page + save::PAGES - 1) % save::PAGES
+        };
+
+        self.ui().refresh(world, self.mode, self.page);
+    }
+
+    ///Handles click on slot.
+    ///
+    ///Returns whether screen should be closed.
+    fn pick(&mut self, world: &mut amethyst::prelude::World, slot: u32) -> bool {
+        match self.mode {
+            Mode::Save => {
+                let snapshot = match self.snapshot.as_mut() {
+                    Some(snapshot) => snapshot,
+                    None => unreach!(),
+                };
+                snapshot.timestamp = save::Save::now();
+
+                match snapshot.write(slot) {
+                    Ok(()) => info!("Saved into slot {}", slot),
+                    Err(error) => error!("Unable to save: {}", error),
+                }
+                store_seen(world);
+
+                self.ui().refresh(world, self.mode, self.page);
+                false
+            },
+            Mode::Load => match save::Save::read(slot) {
+                Ok(save) => {
+                    world.write_resource::<save::Pending>().0 = Some(save);
+                    true
+                },
+                Err(error) => {
+                    warn!("Unable to load slot {}: {}", slot, error);
+                    false
+                }
+            },
+        }
+    }
+}
+
+impl<'a, 'b> amethyst::State<GameData<'a, 'b>, amethyst::StateEvent> for Slots {
+    fn on_start(&mut self, mut data: amethyst::StateData<GameData>) {
+        components::scope::enter(&mut data.world);
+
+        let res = components::ui::Resources::fetch(&mut data.world);
+        let ui = match components::ui::Slots::new(&mut data.world, &res, self.mode, self.page) {
+            Ok(ui) => ui,
+            Err(error) => {
+                error!("Unable to load save slots layout: {}", error);
+                components::ui::Slots::builtin(&mut data.world, &res, self.mode, self.page)
+            }
+        };
+        self.ui = Some(ui);
+    }
+
+    fn on_stop(&mut self, mut data: amethyst::StateData<GameData>) {
+        match self.ui.take() {
+            Some(ui) => ui.destroy(&mut data.world),
+            None => unreach!()
+        }
+
+        components::scope::exit(&mut data.world);
+    }
+
+    fn handle_event(&mut self, mut data: amethyst::StateData<GameData>, event: amethyst::StateEvent) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        match event {
+            amethyst::StateEvent::Window(event) => if amethyst::input::is_close_requested(&event) {
+                amethyst::Trans::Quit
+            } else if amethyst::input::is_key_down(&event, amethyst::renderer::VirtualKeyCode::Escape) || input::is_stage_click(&event, amethyst::renderer::MouseButton::Right, &data.world) {
+                amethyst::Trans::Pop
+            } else {
+                amethyst::Trans::None
+            },
+            amethyst::StateEvent::Ui(event) => match event.event_type {
+                amethyst::ui::UiEventType::ClickStop => {
+                    if event.target == self.ui().back {
+                        amethyst::Trans::Pop
+                    } else if event.target == self.ui().prev {
+                        self.turn_page(&mut data.world, false);
+                        amethyst::Trans::None
+                    } else if event.target == self.ui().next {
+                        self.turn_page(&mut data.world, true);
+                        amethyst::Trans::None
+                    } else if let Some(idx) = self.ui().find(event.target) {
+                        let slot = components::ui::Slots::slot(self.page, idx);
+                        if self.pick(&mut data.world, slot) {
+                            amethyst::Trans::Pop
+                        } else {
+                            amethyst::Trans::None
+                        }
+                    } else {
+                        amethyst::Trans::None
+                    }
+                },
+                _ => amethyst::Trans::None
+            },
+        }
+    }
+
+    fn update(&mut self, state: amethyst::StateData<GameData>) -> amethyst::Trans<GameData<'a, 'b>, amethyst::StateEvent> {
+        state.data.update(Base, &state.world.res);
+        amethyst::Trans::None
+    }
+}
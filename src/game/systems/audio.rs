@@ -0,0 +1,18 @@
+use amethyst::core::timing::Time;
+use amethyst::ecs::{System, Read, Write};
+
+use crate::game::audio;
+
+///Advances fades of audio channels.
+#[derive(Default)]
+pub struct Audio;
+
+pub const NAME: &'static str = "Audio-System";
+
+impl<'s> System<'s> for Audio {
+    type SystemData = (Write<'s, audio::Audio>, Read<'s, Time>);
+
+    fn run(&mut self, (mut audio, time): Self::SystemData) {
+        audio.update(time.delta_seconds());
+    }
+}
@@ -0,0 +1,58 @@
+use amethyst::assets::AssetStorage;
+use amethyst::core::Transform;
+use amethyst::ecs::{Entities, Join, System, WriteStorage, Read, ReadStorage};
+use amethyst::renderer::{SpriteRender, SpriteSheet};
+
+use crate::game::components::camera;
+use crate::game::components::stage::{FitHeight, FitStage};
+
+///Scales sprites with `FitHeight` or `FitStage` once their sprite sheets are loaded.
+#[derive(Default)]
+pub struct Fit;
+
+pub const NAME: &'static str = "Fit-System";
+
+impl<'s> System<'s> for Fit {
+    type SystemData = (Entities<'s>, WriteStorage<'s, FitHeight>, WriteStorage<'s, FitStage>, WriteStorage<'s, Transform>, ReadStorage<'s, SpriteRender>, Read<'s, AssetStorage<SpriteSheet>>);
+
+    fn run(&mut self, (entities, mut fits, mut stage_fits, mut transforms, sprites, sheets): Self::SystemData) {
+        let mut fitted = Vec::new();
+
+        for (entity, fit, transform, sprite) in (&*entities, &fits, &mut transforms, &sprites).join() {
+            //Sprite sheet is loaded asynchronously, so wait until it is available
+            let height = match sheets.get(&sprite.sprite_sheet).and_then(|sheet| sheet.sprites.get(sprite.sprite_number)) {
+                Some(sprite) => sprite.height,
+                None => continue,
+            };
+
+            if height > 0.0 {
+                let scale = fit.0 / height;
+                transform.set_scale(scale, scale, 1.0);
+            }
+
+            fitted.push(entity);
+        }
+
+        for entity in fitted.drain(..) {
+            fits.remove(entity);
+        }
+
+        for (entity, fit, transform, sprite) in (&*entities, &stage_fits, &mut transforms, &sprites).join() {
+            let (width, height) = match sheets.get(&sprite.sprite_sheet).and_then(|sheet| sheet.sprites.get(sprite.sprite_number)) {
+                Some(sprite) => (sprite.width, sprite.height),
+                None => continue,
+            };
+
+            if width > 0.0 && height > 0.0 {
+                let (x, y) = fit.0.scale(width, height, camera::WIDTH, camera::HEIGHT);
+                transform.set_scale(x, y, 1.0);
+            }
+
+            fitted.push(entity);
+        }
+
+        for entity in fitted {
+            stage_fits.remove(entity);
+        }
+    }
+}
@@ -1,2 +1,14 @@
-pub mod demo;
-pub use self::demo::Demo;
+pub mod typewriter;
+pub use self::typewriter::Typewriter;
+pub mod fit;
+pub use self::fit::Fit;
+pub mod tween;
+pub use self::tween::Tween;
+pub mod transition;
+pub use self::transition::Transition;
+pub mod audio;
+pub use self::audio::Audio;
+pub mod viewport;
+pub use self::viewport::Viewport;
+pub mod window;
+pub use self::window::Window;
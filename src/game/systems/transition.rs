@@ -0,0 +1,125 @@
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Entities, System, WriteStorage, Read, ReadExpect, Write};
+use amethyst::renderer::{ScreenDimensions, Texture, TextureHandle};
+use amethyst::ui::{Anchor, UiImage, UiTransform};
+
+use crate::game::components::scope::Persistent;
+use crate::game::components::transition::{self, Direction, Kind, Rules, STEPS};
+
+//Above any other UI
+const Z: f32 = 1000.0;
+
+///Advances screen transition and draws its overlay.
+#[derive(Default)]
+pub struct Transition {
+    //Overlay colour and its textures from transparent to opaque
+    color: Option<[f32; 4]>,
+    textures: Vec<TextureHandle>,
+}
+
+pub const NAME: &'static str = "Transition-System";
+
+impl Transition {
+    fn textures(&mut self, color: [f32; 4], loader: &Loader, storage: &AssetStorage<Texture>) -> &[TextureHandle] {
+        if self.color != Some(color) {
+            self.textures = (0..=STEPS).map(|step| {
+                let alpha = color[3] * step as f32 / STEPS as f32;
+                loader.load_from_data([color[0], color[1], color[2], alpha].into(), (), storage)
+            }).collect();
+            self.color = Some(color);
+        }
+
+        &self.textures
+    }
+}
+
+impl<'s> System<'s> for Transition {
+    type SystemData = (Entities<'s>, Write<'s, transition::Transition>, WriteStorage<'s, Persistent>, WriteStorage<'s, UiTransform>, WriteStorage<'s, UiImage>, ReadExpect<'s, Loader>, Read<'s, AssetStorage<Texture>>, ReadExpect<'s, ScreenDimensions>, Read<'s, Time>, Read<'s, Rules>);
+
+    fn run(&mut self, (entities, mut state, mut persistent, mut transforms, mut images, loader, storage, screen, time, rules): Self::SystemData) {
+        if !state.is_active() {
+            if let Some(overlay) = state.overlay.take() {
+                let _ = entities.delete(overlay);
+            }
+            return;
+        }
+
+        state.update(time.delta_seconds());
+
+        let (width, height) = (screen.width(), screen.height());
+        let coverage = state.coverage();
+        let kind = state.kind().clone();
+
+        //Anchor, position and size of overlay
+        let (texture, anchor, position, size) = match kind {
+            Kind::Fade(_) | Kind::Dissolve => {
+                let step = (coverage * STEPS as f32).round() as usize;
+                let texture = self.textures(kind.color(), &loader, &storage)[step.min(STEPS)].clone();
+                (texture, Anchor::Middle, (0.0, 0.0), (width, height))
+            },
+            Kind::Rule { ref mask, softness } => {
+                let step = (state.progress() * STEPS as f32).round() as usize;
+                //Rules are prepared when script is loaded, mask that cannot be loaded falls back to fade
+                let texture = rules.get(mask, softness).map(|rule| rule.texture(step, state.is_revealing()));
+                let texture = match texture {
+                    Some(texture) => texture,
+                    None => {
+                        let step = ((coverage * STEPS as f32).round() as usize).min(STEPS);
+                        self.textures(kind.color(), &loader, &storage)[step].clone()
+                    }
+                };
+                (texture, Anchor::Middle, (0.0, 0.0), (width, height))
+            },
+            Kind::Wipe(direction, _) => {
+                let texture = self.textures(kind.color(), &loader, &storage)[STEPS].clone();
+
+                //Panel enters from one edge while covering and leaves through the opposite one
+                let anchor = match (direction, state.is_revealing()) {
+                    (Direction::Right, false) | (Direction::Left, true) => Anchor::MiddleLeft,
+                    (Direction::Left, false) | (Direction::Right, true) => Anchor::MiddleRight,
+                    (Direction::Up, false) | (Direction::Down, true) => Anchor::BottomMiddle,
+                    (Direction::Down, false) | (Direction::Up, true) => Anchor::TopMiddle,
+                };
+                let (w, h) = match direction {
+                    Direction::Left | Direction::Right => (width * coverage, height),
+                    Direction::Up | Direction::Down => (width, height * coverage),
+                };
+                let position = match anchor {
+                    Anchor::MiddleLeft => (w / 2.0, 0.0),
+                    Anchor::MiddleRight => (-w / 2.0, 0.0),
+                    Anchor::BottomMiddle => (0.0, h / 2.0),
+                    _ => (0.0, -h / 2.0),
+                };
+
+                (texture, anchor, position, (w, h))
+            },
+        };
+
+        let overlay = match state.overlay {
+            Some(overlay) => overlay,
+            None => {
+                //Transition continues while states are switched
+                let overlay = entities.create();
+                let _ = persistent.insert(overlay, Persistent);
+                state.overlay = Some(overlay);
+                overlay
+            }
+        };
+
+        match transforms.get_mut(overlay) {
+            Some(transform) => {
+                transform.anchor = anchor;
+                transform.local_x = position.0;
+                transform.local_y = position.1;
+                transform.width = size.0;
+                transform.height = size.1;
+            },
+            None => {
+                let transform = UiTransform::new("TransitionOverlay".to_owned(), anchor, position.0, position.1, Z, size.0, size.1, 0);
+                let _ = transforms.insert(overlay, transform);
+            }
+        }
+        let _ = images.insert(overlay, UiImage { texture });
+    }
+}
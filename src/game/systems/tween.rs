@@ -0,0 +1,56 @@
+use amethyst::core::Transform;
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Entities, Join, System, WriteStorage, Read, Write};
+use amethyst::renderer::Rgba;
+use amethyst::shrev::EventChannel;
+
+use crate::game::components::tween::{self, State, TweenEvent};
+
+///Advances tweens and removes them once they are finished.
+#[derive(Default)]
+pub struct Tween;
+
+pub const NAME: &'static str = "Tween-System";
+
+impl<'s> System<'s> for Tween {
+    type SystemData = (Entities<'s>, WriteStorage<'s, tween::Tween>, WriteStorage<'s, Transform>, WriteStorage<'s, Rgba>, Read<'s, Time>, Write<'s, EventChannel<TweenEvent>>);
+
+    fn run(&mut self, (entities, mut tweens, mut transforms, mut colors, time, mut events): Self::SystemData) {
+        let mut finished = Vec::new();
+
+        for (entity, tween, transform) in (&*entities, &mut tweens, &mut transforms).join() {
+            if tween.has_alpha() && !colors.contains(entity) {
+                let _ = colors.insert(entity, Rgba(1.0, 1.0, 1.0, 1.0));
+            }
+
+            let translation = *transform.translation();
+            let scale = *transform.scale();
+            let current = State {
+                position: [translation.x, translation.y, translation.z],
+                scale: [scale.x, scale.y, scale.z],
+                rotation: transform.rotation().euler_angles().2,
+                alpha: colors.get(entity).map(|color| color.3).unwrap_or(1.0),
+            };
+
+            let state = tween.update(time.delta_seconds(), current);
+
+            transform.set_xyz(state.position[0], state.position[1], state.position[2]);
+            transform.set_scale(state.scale[0], state.scale[1], state.scale[2]);
+            if state.rotation != current.rotation {
+                transform.set_rotation_euler(0.0, 0.0, state.rotation);
+            }
+            if let Some(color) = colors.get_mut(entity) {
+                color.3 = state.alpha;
+            }
+
+            if tween.is_finished() {
+                finished.push(entity);
+            }
+        }
+
+        for entity in finished {
+            tweens.remove(entity);
+            events.single_write(TweenEvent::Finished(entity));
+        }
+    }
+}
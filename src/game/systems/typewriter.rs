@@ -0,0 +1,22 @@
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Join, System, WriteStorage, Read};
+use amethyst::ui::UiText;
+
+use crate::game::components::typewriter::{self, TextSpeed};
+
+#[derive(Default)]
+pub struct Typewriter;
+
+pub const NAME: &'static str = "Typewriter-System";
+
+impl<'s> System<'s> for Typewriter {
+    type SystemData = (WriteStorage<'s, typewriter::Typewriter>, WriteStorage<'s, UiText>, Read<'s, TextSpeed>, Read<'s, Time>);
+
+    fn run(&mut self, (mut typewriters, mut texts, speed, time): Self::SystemData) {
+        for (typewriter, text) in (&mut typewriters, &mut texts).join() {
+            if typewriter.update(time.delta_seconds(), speed.0) {
+                text.text = typewriter.visible();
+            }
+        }
+    }
+}
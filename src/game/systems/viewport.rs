@@ -0,0 +1,37 @@
+use amethyst::ecs::{Join, System, ReadExpect, ReadStorage, WriteStorage};
+use amethyst::renderer::{Camera, ScreenDimensions};
+use amethyst::ui::UiText;
+
+use crate::game::components::camera::{Viewport as Mapping, VirtualFont};
+
+///Keeps cameras and UI fonts scaled to virtual resolution on window resize.
+///
+///UI elements are resized by their `UiResize`.
+#[derive(Default)]
+pub struct Viewport {
+    dimensions: Option<(f32, f32)>,
+}
+
+pub const NAME: &'static str = "Viewport-System";
+
+impl<'s> System<'s> for Viewport {
+    type SystemData = (WriteStorage<'s, Camera>, WriteStorage<'s, UiText>, ReadStorage<'s, VirtualFont>, ReadExpect<'s, ScreenDimensions>);
+
+    fn run(&mut self, (mut cameras, mut texts, fonts, screen): Self::SystemData) {
+        let dimensions = (screen.width(), screen.height());
+        if self.dimensions == Some(dimensions) {
+            return;
+        }
+        self.dimensions = Some(dimensions);
+
+        let viewport = Mapping::new(dimensions);
+
+        for camera in (&mut cameras).join() {
+            *camera = Camera::from(viewport.projection());
+        }
+
+        for (text, font) in (&mut texts, &fonts).join() {
+            text.font_size = viewport.to_screen(font.0);
+        }
+    }
+}
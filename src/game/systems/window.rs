@@ -0,0 +1,94 @@
+use amethyst::core::shrev::{EventChannel, ReaderId};
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Join, Resources, System, SystemData, Read, ReadExpect, WriteExpect, WriteStorage};
+use amethyst::renderer::{ScreenDimensions, WindowMessages};
+use amethyst::ui::{UiResize, UiTransform};
+use amethyst::winit::{Event, WindowEvent};
+
+use crate::game::input;
+use crate::game::settings::{Settings, WindowMode};
+
+//Window is moved and resized by many small steps, so it is stored once they are over
+const STORE_DELAY: f32 = 1.0;
+
+///Switches window mode on Alt+Enter or F11 and remembers size and position of window.
+///
+///Settings are stored shortly after they are changed.
+#[derive(Default)]
+pub struct Window {
+    reader: Option<ReaderId<Event>>,
+    //Window mode from settings is applied on first run
+    initialized: bool,
+    //Set after switching mode, so that UI is resized once window is changed
+    resize: bool,
+    store: Option<f32>,
+}
+
+pub const NAME: &'static str = "Window-System";
+
+impl<'s> System<'s> for Window {
+    type SystemData = (Read<'s, EventChannel<Event>>, WriteExpect<'s, Settings>, WriteExpect<'s, WindowMessages>, WriteStorage<'s, UiResize>, WriteStorage<'s, UiTransform>, ReadExpect<'s, ScreenDimensions>, Read<'s, Time>);
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+    }
+
+    fn run(&mut self, (events, mut settings, mut messages, mut resizes, mut transforms, screen, time): Self::SystemData) {
+        if self.resize {
+            //Modifying UiResize makes it to be applied again, even if screen size is the same
+            let dims = (screen.width(), screen.height());
+            for (resize, transform) in (&mut resizes, &mut transforms).join() {
+                (resize.function)(transform, dims);
+            }
+            self.resize = false;
+        }
+
+        if !self.initialized {
+            settings.send_window(&mut messages);
+            self.initialized = true;
+        }
+
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => unreach!(),
+        };
+
+        for event in events.read(reader) {
+            if input::is_fullscreen_toggle(event) {
+                settings.toggle_fullscreen();
+                settings.send_window(&mut messages);
+                self.resize = true;
+                self.store = Some(STORE_DELAY);
+                continue;
+            }
+
+            //Only windowed geometry is remembered
+            if settings.window_mode != WindowMode::Windowed {
+                continue;
+            }
+
+            match event {
+                Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
+                    settings.resolution = (size.width.round() as u32, size.height.round() as u32);
+                    self.store = Some(STORE_DELAY);
+                },
+                Event::WindowEvent { event: WindowEvent::Moved(position), .. } => {
+                    settings.window_position = Some((position.x.round() as i32, position.y.round() as i32));
+                    self.store = Some(STORE_DELAY);
+                },
+                _ => (),
+            }
+        }
+
+        if let Some(delay) = self.store.take() {
+            let delay = delay - time.delta_seconds();
+
+            if delay > 0.0 {
+                self.store = Some(delay);
+            } else if let Err(error) = settings.store() {
+                error!("Unable to store settings: {}", error);
+            }
+        }
+    }
+}
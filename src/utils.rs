@@ -16,17 +16,6 @@ macro_rules! unreach {
     })
 }
 
-pub trait AssignOptionIf where Self: Sized {
-    #[inline(always)]
-    fn assign_if(&mut self, new_value: Option<Self>) {
-        if let Some(new_value) = new_value {
-            *self = new_value;
-        }
-    }
-}
-
-impl<T: Sized> AssignOptionIf for T {}
-
 ///Result extensions
 pub trait ResultExt<T, E> {
     ///Returns `Ok` variant assuming that `Err` is unreachable